@@ -0,0 +1,38 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+
+// a file of many small functions exercising calls, blocks and operators
+fn source(fns: usize) -> String {
+	let mut source = String::new();
+
+	for i in 0..fns {
+		source.push_str(&format!(
+			"## Function number {i}.\n\
+			f{i}(a, b) =\n  \
+			  x = g(a, h(b, 1), -a * 2)\n  \
+			  while x > 0 do\n    \
+			    x = x - (a + b) / 2\n  \
+			  if x == 0 then print(\"zero\", x) else k(x, 'c', 1.5)\n\n"
+		));
+	}
+
+	source
+}
+
+fn parse(c: &mut Criterion) {
+	let source = source(1000);
+
+	let mut group = c.benchmark_group("parse");
+	group.throughput(Throughput::Bytes(source.len() as u64));
+
+	group.bench_function("boxed", |b| {
+		b.iter(|| rosy_parse::parse_recovering(black_box(&source)))
+	});
+	group.bench_function("arena", |b| {
+		b.iter(|| rosy_parse::parse_arena(black_box(&source)))
+	});
+
+	group.finish();
+}
+
+criterion_group!(benches, parse);
+criterion_main!(benches);
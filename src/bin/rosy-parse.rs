@@ -0,0 +1,162 @@
+use std::env;
+use std::fs;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use rosy_error::RosyError;
+use rosy_parse::common::inspect::inspect_ast;
+
+const USAGE: &str = "\
+usage: rosy-parse <command> <file>...
+
+commands:
+  tokens <file>     print the token stream with spans
+  ast <file>        print the syntax tree
+  check <file>...   report all errors, failing if there are any
+  fmt <file>        print the file formatted
+
+a file of `-` reads from standard input";
+
+fn main() -> ExitCode {
+	let args: Vec<String> = env::args().skip(1).collect();
+
+	let (command, paths) = match args.split_first() {
+		Some((command, paths)) if !paths.is_empty() => (command.as_str(), paths),
+		_ => return usage(),
+	};
+
+	if command != "check" && paths.len() != 1 {
+		return usage();
+	}
+
+	let mut files = Vec::new();
+
+	for path in paths {
+		match read(path) {
+			Ok(source) => files.push((path.as_str(), source)),
+			Err(err) => {
+				eprintln!("error: cannot read {path}: {err}");
+				return ExitCode::from(2);
+			},
+		}
+	}
+
+	let is_ok = match command {
+		"tokens" => tokens(files[0].0, &files[0].1),
+		"ast" => ast(files[0].0, &files[0].1),
+		"check" => files
+			.iter()
+			.fold(true, |is_ok, (path, source)| check(path, source) && is_ok),
+		"fmt" => fmt(files[0].0, &files[0].1),
+		_ => return usage(),
+	};
+
+	if is_ok {
+		ExitCode::SUCCESS
+	} else {
+		ExitCode::FAILURE
+	}
+}
+
+fn usage() -> ExitCode {
+	eprintln!("{USAGE}");
+	ExitCode::from(2)
+}
+
+fn read(path: &str) -> io::Result<String> {
+	if path == "-" {
+		let mut source = String::new();
+		io::stdin().read_to_string(&mut source)?;
+
+		Ok(source)
+	} else {
+		fs::read_to_string(path)
+	}
+}
+
+fn tokens(path: &str, source: &str) -> bool {
+	let mut is_ok = true;
+
+	for result in rosy_parse::tokenize(source) {
+		match result {
+			Ok(token) => println!("{:<10} {}", token.span.to_string(), token.kind.name()),
+			Err(err) => {
+				report(path, source, &[err]);
+				is_ok = false;
+			},
+		}
+	}
+
+	is_ok
+}
+
+fn ast(path: &str, source: &str) -> bool {
+	let (ast, errors) = rosy_parse::parse_recovering(source);
+
+	println!("{}", inspect_ast(&ast));
+	report(path, source, &errors);
+
+	errors.is_empty()
+}
+
+fn check(path: &str, source: &str) -> bool {
+	let (_, errors) = rosy_parse::parse_recovering(source);
+
+	report(path, source, &errors);
+
+	errors.is_empty()
+}
+
+fn fmt(path: &str, source: &str) -> bool {
+	let (ast, cst, _, mut errors) = rosy_parse::parse_with_cst(source);
+
+	if errors.is_empty() {
+		match rosy_parse::format::format_cst(&ast, &cst) {
+			Ok(formatted) => print!("{formatted}"),
+			Err(err) => errors.push(err),
+		}
+	}
+
+	report(path, source, &errors);
+
+	errors.is_empty()
+}
+
+fn report(path: &str, source: &str, errors: &[RosyError]) {
+	for err in errors {
+		eprintln!("{}", render(path, source, err));
+	}
+}
+
+fn render(path: &str, source: &str, err: &RosyError) -> String {
+	let mut lines = vec![format!("error: {}", err.title)];
+
+	for (msg, range) in &err.labels {
+		let start = range.start.min(source.len());
+		let line_start = source[..start].rfind('\n').map_or(0, |i| i + 1);
+		let line_end = source[start..]
+			.find('\n')
+			.map_or(source.len(), |i| start + i);
+
+		let line = &source[line_start..line_end];
+		let ln = source[..line_start].matches('\n').count() + 1;
+		let col = source[line_start..start].chars().count() + 1;
+
+		// keep tabs so the marker lines up with the source line
+		let padding: String = source[line_start..start]
+			.chars()
+			.map(|c| if c == '\t' { '\t' } else { ' ' })
+			.collect();
+
+		let end = range.end.clamp(start, line_end);
+		let width = source[start..end].chars().count().max(1);
+
+		lines.push(format!("  --> {path}:{ln}:{col}"));
+		lines.push(format!("   | {line}"));
+		lines.push(format!("   | {padding}{} {msg}", "^".repeat(width)));
+	}
+
+	lines.push(format!("   = {}", err.description));
+
+	lines.join("\n")
+}
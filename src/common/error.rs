@@ -1,3 +1,4 @@
+pub mod format;
 pub mod lexer;
 pub mod parser;
 
@@ -0,0 +1,20 @@
+use rosy_error::RosyError;
+
+use crate::common::span::Span;
+
+pub fn misplaced_comment(span: Span) -> RosyError {
+	let title = String::from("misplaced comment");
+
+	let msg = String::from("comment cannot be kept here");
+	let labels = vec![(msg, span.into())];
+
+	let description = String::from(
+		"comments are kept on their own line or at the end of a statement, and block comments also before a token",
+	);
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
@@ -3,6 +3,36 @@ use unicode_names2::name;
 
 use crate::common::span::Span;
 
+pub fn empty_char(span: Span) -> RosyError {
+	let title = String::from("empty character literal");
+
+	let msg = String::from("empty character literal");
+	let labels = vec![(msg, span.into())];
+
+	let description = String::from("character literals must contain exactly one character");
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn float_overflow(span: Span) -> RosyError {
+	let title = String::from("float literal too large");
+
+	let msg = String::from("float literal");
+	let labels = vec![(msg, span.into())];
+
+	let description = format!("float literals must be at most {:e}", f64::MAX);
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
 pub fn inconsistent_indent(indent: usize, span: Span) -> RosyError {
 	let title = String::from("inconsistent indent");
 
@@ -33,6 +63,160 @@ pub fn insufficient_indent(indent: usize, min_indent: usize, span: Span) -> Rosy
 	}
 }
 
+pub fn integer_overflow(span: Span) -> RosyError {
+	let title = String::from("integer literal too large");
+
+	let msg = String::from("integer literal");
+	let labels = vec![(msg, span.into())];
+
+	let description = format!(
+		"integer literals must be in the range 0 to {} ({:#x})",
+		i64::MAX,
+		i64::MAX,
+	);
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn invalid_digit(c: char, radix: u32, span: Span) -> RosyError {
+	let title = String::from("invalid digit");
+
+	let msg = format!("digit: {c}");
+	let labels = vec![(msg, span.into())];
+
+	let description = format!(
+		"{} literals may only contain the digits {}",
+		radix_name(radix),
+		radix_digits(radix),
+	);
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn invalid_escape(span: Span) -> RosyError {
+	let title = String::from("invalid escape sequence");
+
+	let msg = String::from("unknown escape sequence");
+	let labels = vec![(msg, span.into())];
+
+	let description =
+		String::from("valid escape sequences are \\n, \\t, \\\", \\', \\\\ and \\u{...}");
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn invalid_numeric_literal(number: &str, rest: &str, span: Span) -> RosyError {
+	let title = String::from("invalid numeric literal");
+
+	let msg = format!("did you mean `{number} * {rest}`?");
+	let labels = vec![(msg, span.into())];
+
+	let description = String::from("identifiers cannot start with a digit");
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn invalid_numeric_suffix(span: Span) -> RosyError {
+	let title = String::from("invalid numeric literal");
+
+	let msg = String::from("invalid numeric literal");
+	let labels = vec![(msg, span.into())];
+
+	let description = String::from("numbers cannot be followed by identifier characters");
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn invalid_unicode_escape(span: Span) -> RosyError {
+	let title = String::from("invalid unicode escape");
+
+	let msg = String::from("invalid unicode escape");
+	let labels = vec![(msg, span.into())];
+
+	let description = String::from(
+		"unicode escapes must be 1 to 6 hexadecimal digits in braces, forming a valid code point",
+	);
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn malformed_float(span: Span) -> RosyError {
+	let title = String::from("malformed float literal");
+
+	let msg = String::from("expected digits after this");
+	let labels = vec![(msg, span.into())];
+
+	let description = String::from(
+		"float literals need at least one digit after the decimal point and in the exponent",
+	);
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn missing_digits(radix: u32, span: Span) -> RosyError {
+	let title = String::from("missing digits");
+
+	let msg = String::from("expected digits after this");
+	let labels = vec![(msg, span.into())];
+
+	let description = format!(
+		"{} literals need at least one of the digits {}",
+		radix_name(radix),
+		radix_digits(radix),
+	);
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn multi_char(count: usize, span: Span) -> RosyError {
+	let title = String::from("character literal with multiple characters");
+
+	let msg = format!("characters: {count}");
+	let labels = vec![(msg, span.into())];
+
+	let description = String::from(
+		"character literals must contain exactly one character, use a string literal instead",
+	);
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
 pub fn unrecognized_character(c: char, span: Span) -> RosyError {
 	let title = String::from("unrecognized character");
 
@@ -48,3 +232,68 @@ pub fn unrecognized_character(c: char, span: Span) -> RosyError {
 		labels,
 	}
 }
+
+pub fn unterminated_string(span: Span) -> RosyError {
+	let title = String::from("unterminated string");
+
+	let msg = String::from("string starts here");
+	let labels = vec![(msg, span.into())];
+
+	let description =
+		String::from("string literal is missing a closing \" before the end of the line");
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn unterminated_char(span: Span) -> RosyError {
+	let title = String::from("unterminated character literal");
+
+	let msg = String::from("character literal starts here");
+	let labels = vec![(msg, span.into())];
+
+	let description =
+		String::from("character literal is missing a closing ' before the end of the line");
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn unterminated_comment(span: Span) -> RosyError {
+	let title = String::from("unterminated comment");
+
+	let msg = String::from("block comment starts here");
+	let labels = vec![(msg, span.into())];
+
+	let description = String::from("block comment is never closed with ]#");
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+fn radix_name(radix: u32) -> &'static str {
+	match radix {
+		2 => "binary",
+		8 => "octal",
+		16 => "hexadecimal",
+		_ => "decimal",
+	}
+}
+
+fn radix_digits(radix: u32) -> &'static str {
+	match radix {
+		2 => "0 and 1",
+		8 => "0 to 7",
+		16 => "0 to 9 and a to f",
+		_ => "0 to 9",
+	}
+}
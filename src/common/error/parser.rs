@@ -2,6 +2,7 @@ use rosy_error::RosyError;
 
 use crate::common::span::Span;
 use crate::lexer::token::TokenKind;
+use crate::parser::ast::{MAX_PREC, MIN_PREC};
 
 pub fn invalid_declaration(kind: &TokenKind, span1: Span, span2: Span) -> RosyError {
 	let title = String::from("invalid declaration");
@@ -70,6 +71,41 @@ pub fn invalid_infix_expr(kind: &TokenKind, span: Span) -> RosyError {
 	}
 }
 
+pub fn invalid_operator(kind: &TokenKind, span: Span) -> RosyError {
+	let title = String::from("invalid operator");
+
+	let name = kind.name();
+	let msg = format!("found: {}", name);
+	let labels = vec![(msg, span.into())];
+
+	let description = format!("expected operator, but found {}", name);
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
+pub fn invalid_precedence(kind: &TokenKind, span: Span) -> RosyError {
+	let title = String::from("invalid precedence");
+
+	let name = kind.name();
+	let msg = format!("found: {}", name);
+	let labels = vec![(msg, span.into())];
+
+	let description = format!(
+		"precedence must be an integer from {} to {}",
+		MIN_PREC, MAX_PREC,
+	);
+
+	RosyError {
+		title,
+		description,
+		labels,
+	}
+}
+
 pub fn invalid_type(kind: &TokenKind, span: Span) -> RosyError {
 	let title = String::from("invalid type");
 
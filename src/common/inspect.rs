@@ -1,220 +1,265 @@
-use indoc::formatdoc;
-
+use crate::common::span::Span;
 use crate::parser::ast::*;
+use crate::parser::visit::*;
 
 pub fn inspect_ast(ast: &Ast) -> String {
-	let mut lines = vec![String::from("╰ AST")];
-
-	for i in 0..ast.len() {
-		let prefix = String::from("  ");
-		let is_last = i == ast.len() - 1;
+	let mut inspector = Inspector {
+		lines: vec![String::from("╰ AST")],
+		prefix: String::new(),
+		is_last: false,
+	};
 
-		let str = match &ast[i].kind {
-			DeclKind::Ty(ty_decl) => inspect_ty_decl(ty_decl, prefix, is_last),
-			DeclKind::Fn(fn_def) => inspect_fn_def(fn_def, prefix, is_last),
-		};
+	inspector.visit_ast(ast);
 
-		lines.push(str);
-	}
+	inspector.lines.join("\n")
+}
 
-	lines.join("\n")
+struct Inspector {
+	lines: Vec<String>,
+	prefix: String,
+	is_last: bool,
 }
 
-fn inspect_ty_decl(ty_decl: &TyDecl, prefix: String, is_last: bool) -> String {
-	let (p1, p2) = prefixes(prefix, is_last);
-
-	let TyDecl {
-		identifier,
-		ty,
-		span,
-	} = ty_decl;
-
-	formatdoc! {"
-		{p1}Type Declaration
-		{p2}│╰ {span}
-		{p2}├ Identifier
-		{}
-		{p2}╰ Type
-		{p2}  │╰ {}
-		{p2}  ╰ {}"
-	,
-		inspect_identifier(&identifier, format!("{p2}│ "), true),
-		ty.span,
-		inspect_ty(&ty),
+impl Inspector {
+	fn push(&mut self, line: String) {
+		self.lines.push(line);
 	}
-}
 
-fn inspect_fn_def(fn_def: &FnDef, prefix: String, is_last: bool) -> String {
-	let (p1, p2) = prefixes(prefix, is_last);
+	// sets the position of the next node to be visited
+	fn at(&mut self, prefix: String, is_last: bool) -> &mut Self {
+		self.prefix = prefix;
+		self.is_last = is_last;
+		self
+	}
 
-	let FnDef {
-		func,
-		params,
-		body,
-		span,
-	} = fn_def;
+	fn prefixes(&self) -> (String, String) {
+		prefixes(self.prefix.clone(), self.is_last)
+	}
 
-	let mut inspect_params: Vec<String> = Vec::new();
+	fn docs(&mut self, docs: &[DocComment], prefix: &str) {
+		if !docs.is_empty() {
+			self.push(format!("{prefix}├ Docs"));
+		}
 
-	for i in 0..params.len() {
-		let prefix = format!("{p2}│ ");
-		let is_last = i == params.len() - 1;
+		for (i, doc) in docs.iter().enumerate() {
+			let (p1, p2) = prefixes(format!("{prefix}│ "), i == docs.len() - 1);
 
-		let str = inspect_identifier(&params[i], prefix, is_last);
+			let DocComment { text, span } = doc;
 
-		inspect_params.push(str);
+			self.push(format!("{p1}Doc({text:?})"));
+			self.push(format!("{p2} ╰ {span}"));
+		}
 	}
 
-	formatdoc! {"
-		{p1}Define
-		{p2}│╰ {span}
-		{p2}├ Function
-		{}
-		{p2}├ Parameters
-		{}
-		{p2}╰ Body
-		{}"
-	,
-		inspect_identifier(&func, format!("{p2}│ "), true),
-		if inspect_params.len() == 0 {
-			format!("{p2}│")
-		} else {
-			inspect_params.join("\n")
-		},
-		inspect_expr(&body, format!("{p2}  "), true),
+	fn leaf(&mut self, name: String, span: &Span) {
+		let (p1, p2) = self.prefixes();
+
+		self.push(format!("{p1}{name}"));
+		self.push(format!("{p2} ╰ {span}"));
 	}
 }
 
-fn inspect_var_def(var_def: &VarDef, prefix: String, is_last: bool) -> String {
-	let (p1, p2) = prefixes(prefix, is_last);
-
-	let VarDef { var, value, span } = var_def;
-
-	formatdoc! {"
-		{p1}Define
-		{p2}│╰ {span}
-		{p2}├ Variable
-		{}
-		{p2}╰ Value
-		{}"
-	,
-		inspect_identifier(&var, format!("{p2}│ "), true),
-		inspect_expr(&value, format!("{p2}  "), true),
+impl Visitor<'_> for Inspector {
+	fn visit_ast(&mut self, ast: &Ast) {
+		for i in 0..ast.len() {
+			self.at(String::from("  "), i == ast.len() - 1)
+				.visit_decl(&ast[i]);
+		}
 	}
-}
 
-fn inspect_expr(expr: &Expr, prefix: String, is_last: bool) -> String {
-	let (p1, p2) = prefixes(prefix, is_last);
+	fn visit_decl(&mut self, decl: &Decl) {
+		match &decl.kind {
+			DeclKind::Error => self.leaf(String::from("Error"), &decl.span),
+			_ => walk_decl(self, decl),
+		}
+	}
 
-	let Expr { kind, span } = expr;
+	fn visit_ty_decl(&mut self, ty_decl: &TyDecl) {
+		let (p1, p2) = self.prefixes();
+
+		let TyDecl {
+			identifier,
+			ty,
+			docs,
+			span,
+		} = ty_decl;
+
+		self.push(format!("{p1}Type Declaration"));
+		self.push(format!("{p2}│╰ {span}"));
+		self.docs(docs, &p2);
+		self.push(format!("{p2}├ Identifier"));
+		self.at(format!("{p2}│ "), true)
+			.visit_identifier(identifier);
+		self.push(format!("{p2}╰ Type"));
+		self.push(format!("{p2}  │╰ {}", ty.span));
+		self.push(format!("{p2}  ╰ {}", inspect_ty(ty)));
+	}
 
-	match kind {
-		ExprKind::Boolean(b) => formatdoc! {"
-			{p1}Boolean({b})
-			{p2} ╰ {span}"
-		},
-		ExprKind::Integer(i) => formatdoc! {"
-			{p1}Integer({i})
-			{p2} ╰ {span}"
-		},
-		ExprKind::Identifier(i) => formatdoc! {"
-			{p1}Identifier({i})
-			{p2} ╰ {span}"
-		},
-		ExprKind::If(cond, t, e) => {
-			if let Some(e) = e {
-				formatdoc! {"
-					{p1}If
-					{p2}│╰ {span}
-					{p2}├ Condition
-					{}
-					{p2}├ Then
-					{}
-					{p2}╰ Else
-					{}"
-				,
-					inspect_expr(&cond, format!("{p2}│ "), true),
-					inspect_expr(&t, format!("{p2}│ "), true),
-					inspect_expr(&e, format!("{p2}  "), true),
+	fn visit_fn_def(&mut self, fn_def: &FnDef) {
+		let (p1, p2) = self.prefixes();
+
+		let FnDef {
+			func,
+			params,
+			body,
+			docs,
+			span,
+		} = fn_def;
+
+		self.push(format!("{p1}Define"));
+		self.push(format!("{p2}│╰ {span}"));
+		self.docs(docs, &p2);
+		self.push(format!("{p2}├ Function"));
+		self.at(format!("{p2}│ "), true).visit_identifier(func);
+		self.push(format!("{p2}├ Parameters"));
+
+		if params.is_empty() {
+			self.push(format!("{p2}│"));
+		}
+
+		for i in 0..params.len() {
+			self.at(format!("{p2}│ "), i == params.len() - 1)
+				.visit_identifier(&params[i]);
+		}
+
+		self.push(format!("{p2}╰ Body"));
+		self.at(format!("{p2}  "), true).visit_expr(body);
+	}
+
+	fn visit_fixity_decl(&mut self, fixity_decl: &FixityDecl) {
+		let (p1, p2) = self.prefixes();
+
+		let FixityDecl {
+			assoc,
+			prec,
+			ops,
+			docs,
+			span,
+		} = fixity_decl;
+
+		self.push(format!("{p1}Fixity"));
+		self.push(format!("{p2}│╰ {span}"));
+		self.docs(docs, &p2);
+		self.push(format!("{p2}├ Associativity({assoc:?})"));
+		self.push(format!("{p2}├ Precedence({prec})"));
+		self.push(format!("{p2}╰ Operators"));
+
+		for i in 0..ops.len() {
+			self.at(format!("{p2}  "), i == ops.len() - 1)
+				.visit_identifier(&ops[i]);
+		}
+	}
+
+	fn visit_var_def(&mut self, var_def: &VarDef) {
+		let (p1, p2) = self.prefixes();
+
+		let VarDef { var, value, span } = var_def;
+
+		self.push(format!("{p1}Define"));
+		self.push(format!("{p2}│╰ {span}"));
+		self.push(format!("{p2}├ Variable"));
+		self.at(format!("{p2}│ "), true).visit_identifier(var);
+		self.push(format!("{p2}╰ Value"));
+		self.at(format!("{p2}  "), true).visit_expr(value);
+	}
+
+	fn visit_expr(&mut self, expr: &Expr) {
+		let (p1, p2) = self.prefixes();
+
+		let Expr { kind, span, .. } = expr;
+
+		match kind {
+			ExprKind::Boolean(b) => self.leaf(format!("Boolean({b})"), span),
+			ExprKind::Integer(i) => self.leaf(format!("Integer({i})"), span),
+			ExprKind::Float(f) => self.leaf(format!("Float({f:?})"), span),
+			ExprKind::String(s) => self.leaf(format!("String({s:?})"), span),
+			ExprKind::Char(c) => self.leaf(format!("Char({c:?})"), span),
+			ExprKind::Identifier(i) => self.leaf(format!("Identifier({i})"), span),
+			ExprKind::If(cond, t, e) => {
+				self.push(format!("{p1}If"));
+				self.push(format!("{p2}│╰ {span}"));
+				self.push(format!("{p2}├ Condition"));
+				self.at(format!("{p2}│ "), true).visit_expr(cond);
+
+				if let Some(e) = e {
+					self.push(format!("{p2}├ Then"));
+					self.at(format!("{p2}│ "), true).visit_expr(t);
+					self.push(format!("{p2}╰ Else"));
+					self.at(format!("{p2}  "), true).visit_expr(e);
+				} else {
+					self.push(format!("{p2}╰ Then"));
+					self.at(format!("{p2}  "), true).visit_expr(t);
 				}
-			} else {
-				formatdoc! {"
-					{p1}If
-					{p2}│╰ {span}
-					{p2}├ Condition
-					{}
-					{p2}╰ Then
-					{}"
-				,
-					inspect_expr(&cond, format!("{p2}│ "), true),
-					inspect_expr(&t, format!("{p2}  "), true),
+			},
+			ExprKind::While(cond, expr) => {
+				self.push(format!("{p1}While"));
+				self.push(format!("{p2}│╰ {span}"));
+				self.push(format!("{p2}├ Condition"));
+				self.at(format!("{p2}│ "), true).visit_expr(cond);
+				self.push(format!("{p2}╰ Body"));
+				self.at(format!("{p2}  "), true).visit_expr(expr);
+			},
+			ExprKind::Call(func, args) => {
+				self.push(format!("{p1}Call"));
+				self.push(format!("{p2}│╰ {span}"));
+				self.push(format!("{p2}├ Function"));
+				self.at(format!("{p2}│ "), true).visit_expr(func);
+				self.push(format!("{p2}╰ Arguments"));
+
+				if args.is_empty() {
+					self.push(p2.clone());
 				}
-			}
-		},
-		ExprKind::While(cond, expr) => {
-			formatdoc! {"
-				{p1}While
-				{p2}│╰ {span}
-				{p2}├ Condition
-				{}
-				{p2}╰ Body
-				{}"
-			,
-				inspect_expr(&cond, format!("{p2}│ "), true),
-				inspect_expr(&expr, format!("{p2}  "), true),
-			}
-		},
-		ExprKind::Call(func, args) => {
-			let mut inspect_args: Vec<String> = Vec::new();
-
-			for i in 0..args.len() {
-				let prefix = format!("{p2}  ");
-				let is_last = i == args.len() - 1;
-
-				let str = inspect_expr(&args[i], prefix, is_last);
-
-				inspect_args.push(str);
-			}
-
-			formatdoc! {"
-				{p1}Call
-				{p2}│╰ {span}
-				{p2}├ Function
-				{}
-				{p2}╰ Arguments
-				{}"
-			,
-				inspect_expr(&func, format!("{p2}│ "), true),
-				if inspect_args.len() == 0 {
-					format!("{p2}")
-				} else {
-					inspect_args.join("\n")
-				},
-			}
-		},
-		ExprKind::Block(stmts) => {
-			let mut inspect_stmts: Vec<String> = Vec::new();
-
-			for i in 0..stmts.len() {
-				let prefix = p2.clone();
-				let is_last = i == stmts.len() - 1;
-
-				let str = match &stmts[i].kind {
-					StmtKind::TyDecl(ty_decl) => inspect_ty_decl(ty_decl, prefix, is_last),
-					StmtKind::VarDef(var_def) => inspect_var_def(var_def, prefix, is_last),
-					StmtKind::Expr(expr) => inspect_expr(expr, prefix, is_last),
-				};
-
-				inspect_stmts.push(str);
-			}
-
-			formatdoc! {"
-				{p1}Block
-				{p2}│╰ {span}
-				{}",
-				inspect_stmts.join("\n"),
-			}
-		},
+
+				for i in 0..args.len() {
+					self.at(format!("{p2}  "), i == args.len() - 1)
+						.visit_expr(&args[i]);
+				}
+			},
+			ExprKind::Unary(un_op, operand) => {
+				let UnaryOp {
+					lexeme,
+					span: op_span,
+				} = un_op;
+
+				self.push(format!("{p1}Unary"));
+				self.push(format!("{p2}│╰ {span}"));
+				self.push(format!("{p2}├ Operator({lexeme})"));
+				self.push(format!("{p2}│  ╰ {op_span}"));
+				self.push(format!("{p2}╰ Operand"));
+				self.at(format!("{p2}  "), true).visit_expr(operand);
+			},
+			ExprKind::Binary(bin_op, lhs, rhs) => {
+				let BinaryOp {
+					lexeme,
+					span: op_span,
+				} = bin_op;
+
+				self.push(format!("{p1}Binary"));
+				self.push(format!("{p2}│╰ {span}"));
+				self.push(format!("{p2}├ Operator({lexeme})"));
+				self.push(format!("{p2}│  ╰ {op_span}"));
+				self.push(format!("{p2}├ Left"));
+				self.at(format!("{p2}│ "), true).visit_expr(lhs);
+				self.push(format!("{p2}╰ Right"));
+				self.at(format!("{p2}  "), true).visit_expr(rhs);
+			},
+			ExprKind::Error => self.leaf(String::from("Error"), span),
+			ExprKind::Block(stmts) => {
+				self.push(format!("{p1}Block"));
+				self.push(format!("{p2}│╰ {span}"));
+
+				for i in 0..stmts.len() {
+					self.at(p2.clone(), i == stmts.len() - 1)
+						.visit_stmt(&stmts[i]);
+				}
+			},
+		}
+	}
+
+	fn visit_identifier(&mut self, identifier: &Identifier) {
+		let Identifier { name, span, .. } = identifier;
+
+		self.leaf(format!("Identifier({name})"), span);
 	}
 }
 
@@ -249,17 +294,6 @@ fn inspect_ty(ty: &Ty) -> String {
 	}
 }
 
-fn inspect_identifier(identifier: &Identifier, prefix: String, is_last: bool) -> String {
-	let (p1, p2) = prefixes(prefix, is_last);
-
-	let Identifier { name, span } = identifier;
-
-	formatdoc! {"
-		{p1}Identifier({name})
-		{p2} ╰ {span}"
-	}
-}
-
 fn prefixes(prefix: String, is_last: bool) -> (String, String) {
 	let p1 = if is_last {
 		format!("{prefix}╰ ")
@@ -1,9 +1,22 @@
 use std::fmt::{Display, Formatter, Result};
 use std::ops::Range;
 
-#[derive(Clone, Copy, Debug)]
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(from = "SpanRepr", into = "SpanRepr"))]
 pub struct Span(pub usize, pub usize);
 
+// spans are serialized as `{"start": .., "end": ..}` rather than a tuple
+#[cfg(feature = "serde")]
+#[derive(Serialize, Deserialize)]
+struct SpanRepr {
+	start: usize,
+	end: usize,
+}
+
 impl Span {
 	pub fn new(start: usize, end: usize) -> Self {
 		Self(start, end)
@@ -29,3 +42,20 @@ impl From<Span> for Range<usize> {
 		span.0..span.1
 	}
 }
+
+#[cfg(feature = "serde")]
+impl From<Span> for SpanRepr {
+	fn from(span: Span) -> SpanRepr {
+		SpanRepr {
+			start: span.0,
+			end: span.1,
+		}
+	}
+}
+
+#[cfg(feature = "serde")]
+impl From<SpanRepr> for Span {
+	fn from(repr: SpanRepr) -> Span {
+		Span(repr.start, repr.end)
+	}
+}
@@ -0,0 +1,584 @@
+use std::collections::{HashMap, HashSet};
+
+use crate::common::error::format::misplaced_comment;
+use crate::common::error::R;
+use crate::common::span::Span;
+use crate::parser::ast::*;
+use crate::parser::cst::{LeafKind, SyntaxNode, TriviaKind};
+use crate::parser::visit::{self, Visitor};
+
+pub const MAX_WIDTH: usize = 80;
+
+const INDENT: &str = "  ";
+
+/// Renders `ast` as canonical Rosy source. Parentheses are inserted only where
+/// the precedence and associativity of operators require them, and numbers are
+/// written in decimal. Doc comments are kept, while plain comments are not part
+/// of the AST and are dropped; use [`format_cst`] to keep them.
+pub fn format_ast(ast: &Ast) -> String {
+	let mut formatter = Formatter::new(ast, String::new(), Vec::new());
+	formatter.format_decls(ast);
+
+	formatter.out
+}
+
+/// Renders `ast` like [`format_ast`], keeping the plain comments of `cst`, the
+/// lossless tree parsed along with it. Comments on their own line stay before
+/// the next declaration or statement, comments ending a line stay at the end
+/// of it, and block comments inside an expression stay before the next token.
+/// Any other comment is an error rather than being dropped.
+pub fn format_cst(ast: &Ast, cst: &SyntaxNode) -> R<String> {
+	let mut docs = DocSpans {
+		spans: HashSet::new(),
+	};
+	docs.visit_ast(ast);
+
+	let comments = cst
+		.tokens()
+		.into_iter()
+		.filter(|token| match token.kind() {
+			LeafKind::Trivia(TriviaKind::Comment) => true,
+			LeafKind::Trivia(TriviaKind::DocComment) => !docs.spans.contains(&token.span()),
+			_ => false,
+		})
+		.map(|token| Comment {
+			text: String::from(token.text().trim_end()),
+			span: token.span(),
+		})
+		.collect();
+
+	let mut formatter = Formatter::new(ast, cst.to_string(), comments);
+	formatter.format_decls(ast);
+	formatter.leading_comments(usize::MAX);
+
+	match formatter.misplaced {
+		Some(span) => Err(misplaced_comment(span)),
+		None => Ok(formatter.out),
+	}
+}
+
+#[derive(Clone)]
+struct Comment {
+	text: String,
+	span: Span,
+}
+
+struct Formatter {
+	out: String,
+	indent: usize,
+	is_flat: bool,
+	fixities: HashMap<String, (OpAssoc, usize)>,
+	source: String,
+	comments: Vec<Comment>,
+	next_comment: usize,
+	// end of the last source token that was written, so comments before it
+	// can no longer be placed
+	last_end: usize,
+	// a line comment was written and nothing may follow it on the line
+	line_comment: Option<Span>,
+	misplaced: Option<Span>,
+}
+
+impl Formatter {
+	fn new(ast: &Ast, source: String, comments: Vec<Comment>) -> Self {
+		Self {
+			out: String::new(),
+			indent: 0,
+			is_flat: false,
+			fixities: fixities(ast),
+			source,
+			comments,
+			next_comment: 0,
+			last_end: 0,
+			line_comment: None,
+			misplaced: None,
+		}
+	}
+
+	fn format_decls(&mut self, ast: &Ast) {
+		for i in 0..ast.len() {
+			if i > 0 {
+				self.newline();
+
+				// a type declaration stays attached to the function it annotates
+				let is_signature = match (&ast[i - 1].kind, &ast[i].kind) {
+					(DeclKind::Ty(ty_decl), DeclKind::Fn(fn_def)) => {
+						ty_decl.identifier.name == fn_def.func.name
+					},
+					_ => false,
+				};
+
+				if !is_signature {
+					self.newline();
+				}
+			}
+
+			self.leading_comments(ast[i].span.0);
+			self.format_docs(ast[i].docs());
+
+			match &ast[i].kind {
+				DeclKind::Ty(ty_decl) => self.format_ty_decl(ty_decl),
+				DeclKind::Fn(fn_def) => self.format_fn_def(fn_def),
+				DeclKind::Fixity(fixity_decl) => {
+					self.format_fixity_decl(fixity_decl);
+					self.last_end = fixity_decl.span.1;
+				},
+				DeclKind::Error => self.write("<error>"),
+			}
+
+			self.trailing_comments();
+		}
+
+		if !ast.is_empty() {
+			self.newline();
+		}
+	}
+
+	fn format_docs(&mut self, docs: &[DocComment]) {
+		for doc in docs {
+			if doc.text.is_empty() {
+				self.write("##");
+			} else {
+				self.write(&format!("## {}", doc.text));
+			}
+
+			self.newline();
+		}
+	}
+
+	fn format_stmt(&mut self, stmt: &Stmt) {
+		match &stmt.kind {
+			StmtKind::TyDecl(ty_decl) => {
+				self.format_docs(&ty_decl.docs);
+				self.format_ty_decl(ty_decl);
+			},
+			StmtKind::VarDef(var_def) => {
+				self.write(&format!("{} =", var_def.var.name));
+				self.last_end = var_def.var.span.1;
+				self.format_body(&var_def.value);
+			},
+			StmtKind::Expr(expr) => self.format_expr(expr),
+		}
+	}
+
+	fn format_ty_decl(&mut self, ty_decl: &TyDecl) {
+		self.write(&format!("{}: ", ty_decl.identifier.name));
+		self.format_ty(&ty_decl.ty);
+		self.last_end = ty_decl.span.1;
+	}
+
+	fn format_fn_def(&mut self, fn_def: &FnDef) {
+		let params: Vec<_> = fn_def
+			.params
+			.iter()
+			.map(|param| param.name.as_str())
+			.collect();
+
+		self.write(&format!("{}({}) =", fn_def.func.name, params.join(", ")));
+		self.last_end = fn_def.func.span.1;
+		self.format_body(&fn_def.body);
+	}
+
+	fn format_fixity_decl(&mut self, fixity_decl: &FixityDecl) {
+		let keyword = match fixity_decl.assoc {
+			OpAssoc::Left => "infixl",
+			OpAssoc::Right => "infixr",
+		};
+
+		let ops: Vec<_> = fixity_decl.ops.iter().map(|op| op.name.as_str()).collect();
+
+		self.write(&format!("{keyword} {} {}", fixity_decl.prec, ops.join(" ")));
+	}
+
+	// formats an expression following a layout token, where blocks start on
+	// the next line
+	fn format_body(&mut self, expr: &Expr) {
+		match &expr.kind {
+			ExprKind::Block(stmts) => {
+				self.indent += 1;
+
+				for stmt in stmts {
+					self.newline();
+					self.leading_comments(stmt.span.0);
+					self.format_stmt(stmt);
+					self.trailing_comments();
+				}
+
+				self.indent -= 1;
+			},
+			_ => {
+				self.write(" ");
+				self.format_expr(expr);
+			},
+		}
+	}
+
+	// separates a keyword from the preceding body, which may have been a block
+	fn format_keyword(&mut self, body: &Expr, keyword: &str) {
+		match body.kind {
+			ExprKind::Block(_) => self.newline(),
+			_ => self.write(" "),
+		}
+
+		self.write(keyword);
+	}
+
+	fn format_expr(&mut self, expr: &Expr) {
+		// a parenthesized leaf spans its parentheses, which may hold comments
+		if is_leaf(expr) {
+			self.inline_comments(expr.span.1);
+		}
+
+		match &expr.kind {
+			ExprKind::Boolean(b) => self.write(&b.to_string()),
+			ExprKind::Integer(i) => self.write(&i.to_string()),
+			ExprKind::Float(f) => self.write(&format!("{f:?}")),
+			ExprKind::String(s) => self.write(&quote(s, '"')),
+			ExprKind::Char(c) => self.write(&quote(&c.to_string(), '\'')),
+			ExprKind::Identifier(i) => self.write(i),
+			ExprKind::If(cond, then_expr, else_expr) => {
+				self.write("if");
+				self.format_body(cond);
+				self.format_keyword(cond, "then");
+				self.format_body(then_expr);
+
+				if let Some(else_expr) = else_expr {
+					self.format_keyword(then_expr, "else");
+					self.format_body(else_expr);
+				}
+			},
+			ExprKind::While(cond, body) => {
+				self.write("while");
+				self.format_body(cond);
+				self.format_keyword(cond, "do");
+				self.format_body(body);
+			},
+			ExprKind::Call(func, args) => {
+				let needs_parens = self.binding(func).is_some();
+				self.format_operand(func, needs_parens);
+				self.format_args(args);
+			},
+			ExprKind::Unary(un_op, operand) => {
+				self.write(&un_op.lexeme);
+
+				// keep consecutive operators from lexing as one
+				if matches!(operand.kind, ExprKind::Unary(..)) {
+					self.write(" ");
+				}
+
+				let needs_parens = self.needs_parens_rhs(operand, UNARY_PREC);
+				self.format_operand(operand, needs_parens);
+			},
+			ExprKind::Binary(bin_op, lhs, rhs) => {
+				let (assoc, prec) = self.fixity(bin_op);
+
+				let lhs_parens = match self.binding(lhs) {
+					Some((_, lhs_prec)) => match assoc {
+						OpAssoc::Left => prec > lhs_prec,
+						OpAssoc::Right => prec >= lhs_prec,
+					},
+					None => false,
+				};
+
+				self.format_operand(lhs, lhs_parens);
+				self.inline_comments(bin_op.span.0);
+
+				if !self.out.ends_with(' ') {
+					self.write(" ");
+				}
+
+				self.write(&format!("{} ", bin_op.lexeme));
+				self.last_end = bin_op.span.1;
+
+				let rhs_parens = self.needs_parens_rhs(rhs, prec);
+				self.format_operand(rhs, rhs_parens);
+			},
+			ExprKind::Block(_) => self.format_body(expr),
+			ExprKind::Error => self.write("<error>"),
+		}
+
+		// spans of `if`, `while` and blocks reach past a trailing block
+		if !matches!(
+			expr.kind,
+			ExprKind::If(..) | ExprKind::While(..) | ExprKind::Block(_)
+		) {
+			self.last_end = self.last_end.max(expr.span.1);
+		}
+	}
+
+	fn format_operand(&mut self, expr: &Expr, needs_parens: bool) {
+		if needs_parens {
+			self.write("(");
+			self.format_expr(expr);
+			self.write(")");
+		} else {
+			self.format_expr(expr);
+		}
+	}
+
+	// arguments go on one line if it fits, or one per line otherwise
+	fn format_args(&mut self, args: &[Expr]) {
+		let start = self.out.len();
+		let was_flat = self.is_flat;
+		let comments = (
+			self.next_comment,
+			self.last_end,
+			self.line_comment,
+			self.misplaced,
+		);
+
+		self.is_flat = true;
+
+		self.write("(");
+
+		for (i, arg) in args.iter().enumerate() {
+			if i > 0 {
+				self.write(", ");
+			}
+
+			self.format_expr(arg);
+		}
+
+		self.write(")");
+
+		self.is_flat = was_flat;
+
+		let fits = !self.out[start..].contains('\n') && self.column() <= MAX_WIDTH;
+
+		if fits || self.is_flat || args.is_empty() {
+			return;
+		}
+
+		self.out.truncate(start);
+		(
+			self.next_comment,
+			self.last_end,
+			self.line_comment,
+			self.misplaced,
+		) = comments;
+
+		// continuation lines are indented deeper than the enclosing block, so
+		// they do not produce layout tokens
+		self.write("(");
+		self.indent += 1;
+
+		for (i, arg) in args.iter().enumerate() {
+			self.newline();
+			self.format_expr(arg);
+
+			if i < args.len() - 1 {
+				self.write(",");
+			}
+		}
+
+		self.write(")");
+		self.indent -= 1;
+	}
+
+	fn format_ty(&mut self, ty: &Ty) {
+		match &ty.kind {
+			TyKind::Single(name) => self.write(name),
+			TyKind::Tuple(tys) => self.format_tys(tys),
+			TyKind::Function(params, ret) => {
+				match params.as_slice() {
+					[param] if matches!(param.kind, TyKind::Single(_)) => self.format_ty(param),
+					_ => self.format_tys(params),
+				}
+
+				self.write(" -> ");
+				self.format_ty(ret);
+			},
+		}
+	}
+
+	fn format_tys(&mut self, tys: &[Ty]) {
+		self.write("(");
+
+		for (i, ty) in tys.iter().enumerate() {
+			if i > 0 {
+				self.write(", ");
+			}
+
+			self.format_ty(ty);
+		}
+
+		self.write(")");
+	}
+
+	// how tightly an operand binds to its right; `if` and `while` extend as far
+	// to the right as possible, so they bind the loosest
+	fn binding(&self, expr: &Expr) -> Option<(OpAssoc, usize)> {
+		match &expr.kind {
+			ExprKind::Binary(bin_op, ..) => Some(self.fixity(bin_op)),
+			ExprKind::Unary(..) => Some((OpAssoc::Right, UNARY_PREC)),
+			ExprKind::If(..) | ExprKind::While(..) => Some((OpAssoc::Right, 0)),
+			_ => None,
+		}
+	}
+
+	fn needs_parens_rhs(&self, rhs: &Expr, prec: usize) -> bool {
+		match self.binding(rhs) {
+			Some((OpAssoc::Left, rhs_prec)) => rhs_prec <= prec,
+			Some((OpAssoc::Right, rhs_prec)) => rhs_prec < prec,
+			None => false,
+		}
+	}
+
+	fn fixity(&self, bin_op: &BinaryOp) -> (OpAssoc, usize) {
+		match self.fixities.get(&bin_op.lexeme) {
+			Some(fixity) => *fixity,
+			None => (bin_op.assoc(), bin_op.prec()),
+		}
+	}
+
+	// comments on their own line before the node starting at `start`
+	fn leading_comments(&mut self, start: usize) {
+		while let Some(comment) = self.comments.get(self.next_comment) {
+			if comment.span.0 >= start {
+				break;
+			}
+
+			let comment = self.take_comment();
+
+			if comment.span.0 < self.last_end {
+				self.misplace(comment.span);
+			} else {
+				self.write(&comment.text);
+				self.newline();
+			}
+		}
+	}
+
+	// comments following the last written token on the same source line
+	fn trailing_comments(&mut self) {
+		while let Some(comment) = self.comments.get(self.next_comment) {
+			let is_same_line = comment.span.0 >= self.last_end
+				&& !self.source[self.last_end..comment.span.0].contains('\n');
+
+			if !is_same_line {
+				break;
+			}
+
+			let comment = self.take_comment();
+
+			self.write(&format!(" {}", comment.text));
+			self.last_end = comment.span.1;
+
+			if !comment.text.starts_with("#[") {
+				self.line_comment = Some(comment.span);
+			}
+		}
+	}
+
+	// block comments before `end`, kept on the line of the next token
+	fn inline_comments(&mut self, end: usize) {
+		while let Some(comment) = self.comments.get(self.next_comment) {
+			if comment.span.0 >= end {
+				break;
+			}
+
+			let comment = self.take_comment();
+
+			if comment.span.0 < self.last_end || !comment.text.starts_with("#[") {
+				self.misplace(comment.span);
+				continue;
+			}
+
+			if !self.out.ends_with([' ', '(', '\n']) {
+				self.write(" ");
+			}
+
+			self.write(&format!("{} ", comment.text));
+			self.last_end = comment.span.1;
+		}
+	}
+
+	// comments stay in place, since a call laid out twice takes them again
+	fn take_comment(&mut self) -> Comment {
+		let comment = self.comments[self.next_comment].clone();
+		self.next_comment += 1;
+
+		comment
+	}
+
+	fn misplace(&mut self, span: Span) {
+		self.misplaced.get_or_insert(span);
+	}
+
+	fn write(&mut self, text: &str) {
+		// nothing may follow a line comment on its line
+		if let Some(span) = self.line_comment.take() {
+			self.misplace(span);
+		}
+
+		self.out.push_str(text);
+	}
+
+	fn newline(&mut self) {
+		self.line_comment = None;
+		self.out.push('\n');
+		self.out.push_str(&INDENT.repeat(self.indent));
+	}
+
+	fn column(&self) -> usize {
+		let line_start = self.out.rfind('\n').map_or(0, |i| i + 1);
+		self.out[line_start..].chars().count()
+	}
+}
+
+fn is_leaf(expr: &Expr) -> bool {
+	matches!(
+		expr.kind,
+		ExprKind::Boolean(_)
+			| ExprKind::Integer(_)
+			| ExprKind::Float(_)
+			| ExprKind::String(_)
+			| ExprKind::Char(_)
+			| ExprKind::Identifier(_)
+	)
+}
+
+// attached doc comments are part of the AST and written from there
+struct DocSpans {
+	spans: HashSet<Span>,
+}
+
+impl Visitor<'_> for DocSpans {
+	fn visit_ty_decl(&mut self, ty_decl: &TyDecl) {
+		self.spans.extend(ty_decl.docs.iter().map(|doc| doc.span));
+		visit::walk_ty_decl(self, ty_decl);
+	}
+
+	fn visit_fn_def(&mut self, fn_def: &FnDef) {
+		self.spans.extend(fn_def.docs.iter().map(|doc| doc.span));
+		visit::walk_fn_def(self, fn_def);
+	}
+
+	fn visit_fixity_decl(&mut self, fixity_decl: &FixityDecl) {
+		self.spans
+			.extend(fixity_decl.docs.iter().map(|doc| doc.span));
+		visit::walk_fixity_decl(self, fixity_decl);
+	}
+}
+
+fn quote(text: &str, quote: char) -> String {
+	let mut quoted = String::from(quote);
+
+	for c in text.chars() {
+		match c {
+			'\n' => quoted.push_str("\\n"),
+			'\t' => quoted.push_str("\\t"),
+			'\\' => quoted.push_str("\\\\"),
+			c if c == quote => {
+				quoted.push('\\');
+				quoted.push(c);
+			},
+			c if c.is_control() => quoted.push_str(&format!("\\u{{{:x}}}", c as u32)),
+			c => quoted.push(c),
+		}
+	}
+
+	quoted.push(quote);
+
+	quoted
+}
@@ -1,25 +1,41 @@
 pub mod token;
 
-use std::collections::VecDeque;
+#[cfg(test)]
+mod tests;
+
+use std::collections::{HashMap, VecDeque};
+
+use rosy_error::RosyError;
 
 use crate::common::error::lexer::*;
 use crate::common::error::R;
 use crate::common::span::Span;
 use crate::lexer::token::{Token, TokenKind};
+use crate::parser::ast::DocComment;
 use crate::reader::Reader;
 
-pub struct Lexer {
-	reader: Reader,
-	buffer: VecDeque<R<Token>>,
+const OPERATOR_CHARS: &str = "!$%&*+-./<=>?@^|~";
+
+pub struct Lexer<'a> {
+	reader: Reader<'a>,
+	buffer: VecDeque<Token>,
+	errors: VecDeque<RosyError>,
 	indents: Vec<usize>,
+	pending_docs: Vec<DocComment>,
+	docs: HashMap<usize, Vec<DocComment>>,
+	prev_span: Span,
 }
 
-impl Lexer {
-	pub fn new(reader: Reader) -> Self {
+impl<'a> Lexer<'a> {
+	pub fn new(reader: Reader<'a>) -> Self {
 		let mut lexer = Self {
 			reader,
 			buffer: VecDeque::new(),
+			errors: VecDeque::new(),
 			indents: Vec::new(),
+			pending_docs: Vec::new(),
+			docs: HashMap::new(),
+			prev_span: Span::pair(0),
 		};
 
 		lexer.detect_indent();
@@ -37,13 +53,26 @@ impl Lexer {
 		} else if c.is_ascii_whitespace() {
 			self.reader.skip_whitespace(false);
 			self.generate();
+		} else if self.is_comment() {
+			self.skip_comment();
+			self.generate();
+		} else if c.is_ascii_digit() {
+			let result = self.read_number();
+			let end = self.reader.index;
+
+			let token = Token {
+				kind: result.unwrap_or_else(|err| self.error(err)),
+				span: Span::new(start, end),
+			};
+
+			self.buffer.push_back(token);
+			self.attach_docs(start);
 		} else if self.is_identifier_start() {
 			let mut lexeme = String::new();
 			while self.is_identifier() {
 				lexeme.push(self.reader.next());
 			}
 
-			let is_integer = lexeme.chars().all(|c| c.is_ascii_digit());
 			let mut is_layout_token = false;
 
 			let kind = match lexeme.as_str() {
@@ -69,7 +98,8 @@ impl Lexer {
 					is_layout_token = true;
 					TokenKind::KwDo
 				},
-				_ if is_integer => TokenKind::Integer(lexeme.parse().unwrap()),
+				"infixl" => TokenKind::KwInfixl,
+				"infixr" => TokenKind::KwInfixr,
 				_ => TokenKind::Identifier(lexeme),
 			};
 
@@ -80,11 +110,47 @@ impl Lexer {
 				span: Span::new(start, end),
 			};
 
-			self.buffer.push_back(Ok(token));
+			self.buffer.push_back(token);
+			self.attach_docs(start);
 
 			if is_layout_token {
 				self.prepare_block();
 			}
+		} else if c == '"' {
+			let result = self.read_quoted('"');
+			let end = self.reader.index;
+
+			let token = Token {
+				kind: result.map_or_else(|err| self.error(err), TokenKind::String),
+				span: Span::new(start, end),
+			};
+
+			self.buffer.push_back(token);
+			self.attach_docs(start);
+		} else if c == '\'' {
+			// a quote at the start of a token opens a character literal, while a
+			// quote inside an identifier (e.g. `x'`) is consumed as part of it
+			let result = self.read_quoted('\'');
+			let end = self.reader.index;
+			let span = Span::new(start, end);
+
+			let result = result.and_then(|chars| {
+				let mut iter = chars.chars();
+
+				match (iter.next(), iter.next()) {
+					(Some(c), None) => Ok(TokenKind::Char(c)),
+					(None, _) => Err(empty_char(span)),
+					_ => Err(multi_char(chars.chars().count(), span)),
+				}
+			});
+
+			let token = Token {
+				kind: result.unwrap_or_else(|err| self.error(err)),
+				span,
+			};
+
+			self.buffer.push_back(token);
+			self.attach_docs(start);
 		} else if self.is_symbol() {
 			let kind = match self.reader.next() {
 				'(' => TokenKind::LParen,
@@ -101,7 +167,8 @@ impl Lexer {
 				span: Span::new(start, end),
 			};
 
-			self.buffer.push_back(Ok(token));
+			self.buffer.push_back(token);
+			self.attach_docs(start);
 		} else if self.is_operator() {
 			let mut lexeme = String::new();
 			while self.is_operator() {
@@ -125,7 +192,8 @@ impl Lexer {
 				span: Span::new(start, end),
 			};
 
-			self.buffer.push_back(Ok(token));
+			self.buffer.push_back(token);
+			self.attach_docs(start);
 
 			if is_layout_token {
 				self.prepare_block();
@@ -137,7 +205,7 @@ impl Lexer {
 					span: Span::pair(self.reader.index),
 				};
 
-				self.buffer.push_back(Ok(token));
+				self.buffer.push_back(token);
 				self.indents.pop();
 			}
 
@@ -146,7 +214,7 @@ impl Lexer {
 				span: Span::pair(self.reader.index),
 			};
 
-			self.buffer.push_back(Ok(token));
+			self.buffer.push_back(token);
 		} else {
 			self.reader.next();
 
@@ -155,19 +223,13 @@ impl Lexer {
 
 			let err = unrecognized_character(c, span);
 
-			self.buffer.push_back(Err(err));
+			self.push_error(err, span);
 		}
 	}
 
 	fn prepare_block(&mut self) {
-		macro_rules! col {
-			() => {
-				self.reader.col
-			};
-		}
-
 		let start_ln = self.reader.ln;
-		self.reader.skip_whitespace(true);
+		let col = self.skip_trivia();
 		if self.reader.eof() {
 			return;
 		}
@@ -175,40 +237,34 @@ impl Lexer {
 		let end_ln = self.reader.ln;
 
 		let indent = self.indents[self.indents.len() - 1];
-		if col!() <= indent {
-			let start = self.reader.index - col!() + 1;
+		if col <= indent {
+			let start = self.reader.line_start;
 			let end = self.reader.index;
 
 			let span = Span::new(start, end);
 
-			let err = insufficient_indent(col!() - 1, indent, span);
+			let err = insufficient_indent(col - 1, indent, span);
 
-			self.buffer.push_back(Err(err));
+			self.push_error(err, span);
 		} else if start_ln != end_ln {
 			let token = Token {
 				kind: TokenKind::BlockStart,
 				span: Span::pair(self.reader.index),
 			};
 
-			self.buffer.push_back(Ok(token));
-			self.indents.push(col!());
+			self.buffer.push_back(token);
+			self.indents.push(col);
 		}
 	}
 
 	fn detect_indent(&mut self) {
-		macro_rules! col {
-			() => {
-				self.reader.col
-			};
-		}
-
 		macro_rules! indent {
 			() => {
 				self.indents[self.indents.len() - 1]
 			};
 		}
 
-		self.reader.skip_whitespace(true);
+		let col = self.skip_trivia();
 		if self.reader.eof() {
 			return;
 		}
@@ -217,47 +273,405 @@ impl Lexer {
 			return self.indents.push(1);
 		}
 
-		if col!() < indent!() {
-			while col!() < indent!() {
+		if col < indent!() {
+			while col < indent!() {
 				let token = Token {
 					kind: TokenKind::BlockEnd,
 					span: Span::pair(self.reader.index),
 				};
 
-				self.buffer.push_back(Ok(token));
+				self.buffer.push_back(token);
 				self.indents.pop();
 			}
 
-			if col!() != indent!() {
-				let start = self.reader.index - col!() + 1;
+			if col != indent!() {
+				let start = self.reader.line_start;
 				let end = self.reader.index;
 
 				let span = Span::new(start, end);
 
-				let err = inconsistent_indent(col!() - 1, span);
+				let err = inconsistent_indent(col - 1, span);
 
-				self.buffer.push_back(Err(err))
+				self.push_error(err, span);
 			}
 		}
 
-		if col!() == indent!() {
+		if col == indent!() {
 			let token = Token {
 				kind: TokenKind::Separator,
 				span: Span::pair(self.reader.index),
 			};
 
-			self.buffer.push_back(Ok(token));
+			self.buffer.push_back(token);
+		}
+	}
+
+	fn read_number(&mut self) -> R<TokenKind> {
+		let start = self.reader.index;
+
+		if self.reader.next() == '0' {
+			let radix = match self.reader.peek() {
+				'x' => 16,
+				'o' => 8,
+				'b' => 2,
+				_ => 10,
+			};
+
+			if radix != 10 {
+				self.reader.next();
+				return self.read_radix_integer(start, radix);
+			}
+		}
+
+		self.read_digits();
+
+		let mut is_float = false;
+
+		if self.reader.peek() == '.' {
+			is_float = true;
+			self.reader.next();
+
+			if !self.reader.peek().is_ascii_digit() {
+				let span = Span::new(start, self.reader.index);
+				return Err(malformed_float(span));
+			}
+
+			self.read_digits();
+		}
+
+		// `2exp` is a number next to an identifier rather than an exponent
+		let second = self.reader.peek_second();
+		let starts_name = second.is_ascii_alphabetic() || "_'".contains(second);
+
+		if "eE".contains(self.reader.peek()) && !starts_name {
+			is_float = true;
+			self.reader.next();
+
+			if "+-".contains(self.reader.peek()) {
+				self.reader.next();
+			}
+
+			if !self.reader.peek().is_ascii_digit() {
+				let span = Span::new(start, self.reader.index);
+				return Err(malformed_float(span));
+			}
+
+			self.read_digits();
+		}
+
+		if self.is_identifier() {
+			let number_end = self.reader.index;
+
+			while self.is_identifier() {
+				self.reader.next();
+			}
+
+			// a trailing separator would make the suggestion `2_ * x`
+			let number = self.reader.slice(start, number_end).trim_end_matches('_');
+			let rest = self.reader.slice(number_end, self.reader.index);
+			let span = Span::new(start, self.reader.index);
+
+			// only a name can be multiplied, so `2!` gets no suggestion
+			if !rest.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+				return Err(invalid_numeric_suffix(span));
+			}
+
+			return Err(invalid_numeric_literal(number, rest, span));
+		}
+
+		let lexeme = self.reader.slice(start, self.reader.index).replace('_', "");
+
+		if is_float {
+			let f: f64 = lexeme.parse().unwrap();
+
+			// an infinite value has no literal to be written back as
+			if f.is_infinite() {
+				let span = Span::new(start, self.reader.index);
+				return Err(float_overflow(span));
+			}
+
+			return Ok(TokenKind::Float(f));
+		}
+
+		match lexeme.parse() {
+			Ok(i) => Ok(TokenKind::Integer(i)),
+			Err(_) => {
+				let span = Span::new(start, self.reader.index);
+				Err(integer_overflow(span))
+			},
+		}
+	}
+
+	fn read_radix_integer(&mut self, start: usize, radix: u32) -> R<TokenKind> {
+		let mut digits = String::new();
+		let mut error = None;
+
+		while self.reader.peek().is_ascii_alphanumeric() || self.reader.peek() == '_' {
+			let digit_start = self.reader.index;
+			let c = self.reader.next();
+
+			if c == '_' {
+				continue;
+			}
+
+			if !c.is_digit(radix) {
+				let span = Span::new(digit_start, self.reader.index);
+				error.get_or_insert(invalid_digit(c, radix, span));
+			}
+
+			digits.push(c);
+		}
+
+		if let Some(err) = error {
+			return Err(err);
+		}
+
+		if digits.is_empty() {
+			let span = Span::new(start, self.reader.index);
+			return Err(missing_digits(radix, span));
+		}
+
+		match i64::from_str_radix(&digits, radix) {
+			Ok(i) => Ok(TokenKind::Integer(i)),
+			Err(_) => {
+				let span = Span::new(start, self.reader.index);
+				Err(integer_overflow(span))
+			},
+		}
+	}
+
+	fn read_digits(&mut self) {
+		while self.reader.peek().is_ascii_digit() || self.reader.peek() == '_' {
+			self.reader.next();
+		}
+	}
+
+	fn read_quoted(&mut self, quote: char) -> R<String> {
+		let start = self.reader.index;
+		self.reader.next();
+
+		let mut string = String::new();
+		let mut error = None;
+
+		loop {
+			if self.reader.eof() || self.reader.peek() == '\n' {
+				let span = Span::new(start, self.reader.index);
+
+				return match quote {
+					'"' => Err(unterminated_string(span)),
+					_ => Err(unterminated_char(span)),
+				};
+			}
+
+			match self.reader.peek() {
+				c if c == quote => {
+					self.reader.next();
+					break;
+				},
+				'\\' => match self.read_escape() {
+					Ok(c) => string.push(c),
+					Err(err) => {
+						error.get_or_insert(err);
+					},
+				},
+				_ => string.push(self.reader.next()),
+			}
+		}
+
+		match error {
+			Some(err) => Err(err),
+			None => Ok(string),
+		}
+	}
+
+	fn read_escape(&mut self) -> R<char> {
+		let start = self.reader.index;
+		self.reader.next();
+
+		let c = match self.reader.peek() {
+			'n' => '\n',
+			't' => '\t',
+			'"' => '"',
+			'\'' => '\'',
+			'\\' => '\\',
+			'u' => return self.read_unicode_escape(start),
+			c => {
+				if !self.reader.eof() && c != '\n' {
+					self.reader.next();
+				}
+
+				let span = Span::new(start, self.reader.index);
+				return Err(invalid_escape(span));
+			},
+		};
+
+		self.reader.next();
+
+		Ok(c)
+	}
+
+	fn read_unicode_escape(&mut self, start: usize) -> R<char> {
+		self.reader.next();
+
+		let mut digits = String::new();
+		let mut is_closed = false;
+
+		if self.reader.peek() == '{' {
+			self.reader.next();
+
+			while self.reader.peek().is_ascii_hexdigit() {
+				digits.push(self.reader.next());
+			}
+
+			if self.reader.peek() == '}' {
+				self.reader.next();
+				is_closed = true;
+			}
+		}
+
+		let span = Span::new(start, self.reader.index);
+
+		if !is_closed || digits.is_empty() || digits.len() > 6 {
+			return Err(invalid_unicode_escape(span));
+		}
+
+		u32::from_str_radix(&digits, 16)
+			.ok()
+			.and_then(char::from_u32)
+			.ok_or_else(|| invalid_unicode_escape(span))
+	}
+
+	// skips to the next token, returning the column it is indented to: that
+	// of the first character on its line, even if it is a comment
+	fn skip_trivia(&mut self) -> usize {
+		let mut indent = None;
+
+		loop {
+			self.reader.skip_whitespace(true);
+
+			let line = self.reader.slice(self.reader.line_start, self.reader.index);
+			if line.trim().is_empty() {
+				indent = Some(self.reader.col);
+			}
+
+			if !self.is_comment() {
+				return indent.unwrap_or(self.reader.col);
+			}
+
+			self.skip_comment();
+		}
+	}
+
+	fn skip_comment(&mut self) {
+		let start = self.reader.index;
+
+		self.reader.next();
+
+		// a `##` after code on the same line is a plain comment
+		let is_line_start = self
+			.reader
+			.slice(self.reader.line_start, start)
+			.trim()
+			.is_empty();
+
+		if self.reader.peek() == '#' && is_line_start {
+			self.reader.next();
+
+			if self.reader.peek() == ' ' {
+				self.reader.next();
+			}
+
+			let mut text = String::new();
+			while self.reader.peek() != '\n' && !self.reader.eof() {
+				text.push(self.reader.next());
+			}
+
+			let doc = DocComment {
+				text: String::from(text.trim_end()),
+				span: Span::new(start, self.reader.index),
+			};
+
+			return self.pending_docs.push(doc);
+		}
+
+		if self.reader.peek() != '[' {
+			while self.reader.peek() != '\n' && !self.reader.eof() {
+				self.reader.next();
+			}
+
+			return;
+		}
+
+		self.reader.next();
+
+		let mut depth = 1;
+		while depth > 0 {
+			if self.reader.eof() {
+				let err = unterminated_comment(Span::new(start, start + 2));
+				let span = Span::new(start, self.reader.index);
+
+				return self.push_error(err, span);
+			}
+
+			let c = self.reader.next();
+
+			if c == '#' && self.reader.peek() == '[' {
+				self.reader.next();
+				depth += 1;
+			} else if c == ']' && self.reader.peek() == '#' {
+				self.reader.next();
+				depth -= 1;
+			}
+		}
+	}
+
+	fn error(&mut self, err: RosyError) -> TokenKind {
+		self.errors.push_back(err);
+		TokenKind::Error
+	}
+
+	fn push_error(&mut self, err: RosyError, span: Span) {
+		let token = Token {
+			kind: self.error(err),
+			span,
+		};
+
+		self.buffer.push_back(token);
+	}
+
+	// docs are kept only for a token that can start a declaration
+	fn attach_docs(&mut self, start: usize) {
+		let docs = std::mem::take(&mut self.pending_docs);
+
+		let is_decl_start = matches!(
+			self.buffer.back().map(|token| &token.kind),
+			Some(TokenKind::Identifier(_) | TokenKind::KwInfixl | TokenKind::KwInfixr)
+		);
+
+		if !docs.is_empty() && is_decl_start {
+			self.docs.insert(start, docs);
 		}
 	}
 
+	fn is_comment(&mut self) -> bool {
+		self.reader.peek() == '#'
+	}
+
 	fn is_identifier(&mut self) -> bool {
 		let c = self.reader.peek();
-		self.is_identifier_start() || "!'?".contains(c)
+
+		// `x!=y` is `x != y`, so `!` and `?` starting an operator end the
+		// identifier
+		if "!?".contains(c) {
+			return !OPERATOR_CHARS.contains(self.reader.peek_second());
+		}
+
+		self.is_identifier_start() || c.is_ascii_digit() || c == '\''
 	}
 
 	fn is_identifier_start(&mut self) -> bool {
 		let c = self.reader.peek();
-		c.is_ascii_alphanumeric() || c == '_'
+		c.is_ascii_alphabetic() || c == '_'
 	}
 
 	fn is_symbol(&mut self) -> bool {
@@ -267,35 +681,59 @@ impl Lexer {
 
 	fn is_operator(&mut self) -> bool {
 		let c = self.reader.peek();
-		"!*+-/<=>".contains(c)
+		OPERATOR_CHARS.contains(c)
 	}
 
-	pub fn peek(&mut self) -> R<&Token> {
+	pub fn peek(&mut self) -> &Token {
 		if self.buffer.is_empty() {
 			self.generate();
 		}
 
-		let result = self.buffer.front().unwrap().as_ref();
-		result.map_err(|e| e.clone())
+		self.buffer.front().unwrap()
 	}
 
-	pub fn next(&mut self) -> R<Token> {
+	pub fn next(&mut self) -> Token {
 		if self.buffer.is_empty() {
 			self.generate();
 		}
 
-		self.buffer.pop_front().unwrap()
+		let token = self.buffer.pop_front().unwrap();
+		self.prev_span = token.span;
+
+		token
+	}
+
+	pub fn prev_span(&self) -> Span {
+		self.prev_span
+	}
+
+	pub fn take_docs(&mut self, span: Span) -> Vec<DocComment> {
+		self.docs.remove(&span.0).unwrap_or_default()
+	}
+
+	pub fn take_errors(&mut self) -> Vec<RosyError> {
+		self.errors.drain(..).collect()
 	}
 
 	pub fn restore(&mut self, token: Token) {
-		self.buffer.push_front(Ok(token));
+		self.buffer.push_front(token);
 	}
 
 	pub fn eof(&mut self) -> bool {
-		if let Ok(t) = self.peek() {
-			t.kind == TokenKind::Eof
-		} else {
-			false
+		self.peek().kind == TokenKind::Eof
+	}
+}
+
+impl<'a> Iterator for Lexer<'a> {
+	type Item = R<Token>;
+
+	fn next(&mut self) -> Option<R<Token>> {
+		let token = Lexer::next(self);
+
+		match token.kind {
+			TokenKind::Eof => None,
+			TokenKind::Error => Some(Err(self.errors.pop_front().unwrap())),
+			_ => Some(Ok(token)),
 		}
 	}
 }
@@ -0,0 +1,610 @@
+use std::ops::Range;
+
+use indoc::indoc;
+use rosy_error::RosyError;
+
+use crate::lexer::token::{Token, TokenKind};
+use crate::lexer::Lexer;
+use crate::reader::Reader;
+
+fn tokens(lexer: &mut Lexer) -> Vec<Token> {
+	let mut tokens = Vec::new();
+
+	loop {
+		let token = lexer.next();
+
+		if token.kind == TokenKind::Eof {
+			return tokens;
+		}
+
+		tokens.push(token);
+	}
+}
+
+fn kinds(source: &str) -> Vec<TokenKind> {
+	let mut lexer = Lexer::new(Reader::new(source));
+
+	tokens(&mut lexer)
+		.into_iter()
+		.map(|token| token.kind)
+		.collect()
+}
+
+fn first_error(source: &str) -> RosyError {
+	let mut lexer = Lexer::new(Reader::new(source));
+
+	while lexer.next().kind != TokenKind::Eof {}
+
+	lexer.take_errors().remove(0)
+}
+
+fn ident(name: &str) -> TokenKind {
+	TokenKind::Identifier(String::from(name))
+}
+
+#[test]
+fn line_comments_in_block() {
+	let source = indoc! {"
+		# leading comment
+		f(x) = # trailing comment
+		  # comment before first statement
+		  a # comment after statement
+		  # comment between statements
+		      # deeper comment
+
+		  # shallower comment
+		b
+		  # comment at end of file"
+	};
+
+	let expected = vec![
+		ident("f"),
+		TokenKind::LParen,
+		ident("x"),
+		TokenKind::RParen,
+		TokenKind::Equal,
+		TokenKind::BlockStart,
+		ident("a"),
+		TokenKind::BlockEnd,
+		TokenKind::Separator,
+		ident("b"),
+	];
+
+	assert_eq!(kinds(source), expected);
+}
+
+#[test]
+fn comment_only_lines_do_not_separate() {
+	let source = indoc! {"
+		f() =
+		  a
+		  # comment
+		  b
+		# comment
+		g() = c"
+	};
+
+	let expected = vec![
+		ident("f"),
+		TokenKind::LParen,
+		TokenKind::RParen,
+		TokenKind::Equal,
+		TokenKind::BlockStart,
+		ident("a"),
+		TokenKind::Separator,
+		ident("b"),
+		TokenKind::BlockEnd,
+		TokenKind::Separator,
+		ident("g"),
+		TokenKind::LParen,
+		TokenKind::RParen,
+		TokenKind::Equal,
+		ident("c"),
+	];
+
+	assert_eq!(kinds(source), expected);
+}
+
+#[test]
+fn nested_block_comments() {
+	let source = indoc! {"
+		f() =
+		  a #[ inline ]# b
+		  #[ outer
+		#[ inner ]#
+		  still outer ]#
+		  c"
+	};
+
+	let expected = vec![
+		ident("f"),
+		TokenKind::LParen,
+		TokenKind::RParen,
+		TokenKind::Equal,
+		TokenKind::BlockStart,
+		ident("a"),
+		ident("b"),
+		TokenKind::Separator,
+		ident("c"),
+		TokenKind::BlockEnd,
+	];
+
+	assert_eq!(kinds(source), expected);
+}
+
+#[test]
+fn block_comments_before_code_keep_indent() {
+	let source = indoc! {"
+		f() =
+		  a
+		  #[ c ]# b
+		  #[ d
+		  ]# c
+		#[ e ]#g() = if x then
+		  #[ f ]# y
+	"};
+
+	let expected = vec![
+		ident("f"),
+		TokenKind::LParen,
+		TokenKind::RParen,
+		TokenKind::Equal,
+		TokenKind::BlockStart,
+		ident("a"),
+		TokenKind::Separator,
+		ident("b"),
+		TokenKind::Separator,
+		ident("c"),
+		TokenKind::BlockEnd,
+		TokenKind::Separator,
+		ident("g"),
+		TokenKind::LParen,
+		TokenKind::RParen,
+		TokenKind::Equal,
+		TokenKind::KwIf,
+		ident("x"),
+		TokenKind::KwThen,
+		TokenKind::BlockStart,
+		ident("y"),
+		TokenKind::BlockEnd,
+	];
+
+	assert_eq!(kinds(source), expected);
+}
+
+#[test]
+fn unterminated_block_comment() {
+	let mut lexer = Lexer::new(Reader::new("a #[ #[ ]#"));
+
+	assert_eq!(lexer.next().kind, ident("a"));
+	assert_eq!(lexer.next().kind, TokenKind::Error);
+	assert_eq!(lexer.next().kind, TokenKind::Eof);
+
+	let err = &lexer.take_errors()[0];
+	assert_eq!(err.title, "unterminated comment");
+	assert_eq!(err.labels[0].1, 2..4);
+}
+
+#[test]
+fn doc_comments_attach_to_next_token() {
+	let source = indoc! {"
+		## first line
+		##second line
+		f() = a"
+	};
+
+	let mut lexer = Lexer::new(Reader::new(source));
+	let token = lexer.next();
+	let docs = lexer.take_docs(token.span);
+
+	let texts: Vec<_> = docs.iter().map(|doc| doc.text.as_str()).collect();
+	assert_eq!(texts, vec!["first line", "second line"]);
+	assert_eq!(Range::from(docs[0].span), 0..13);
+
+	assert!(lexer.take_docs(token.span).is_empty());
+}
+
+#[test]
+fn trailing_doc_comments_are_plain() {
+	let source = "f() = 1 ## trailing note on f\ng() = 2\n";
+
+	let mut lexer = Lexer::new(Reader::new(source));
+	let tokens = tokens(&mut lexer);
+
+	assert!(tokens
+		.iter()
+		.all(|token| lexer.take_docs(token.span).is_empty()));
+}
+
+#[test]
+fn doc_comments_before_other_tokens_are_dropped() {
+	let source = indoc! {"
+		## not a declaration
+		1
+		## also not
+		(f)"
+	};
+
+	let mut lexer = Lexer::new(Reader::new(source));
+	let number = lexer.next();
+	let separator = lexer.next();
+	let paren = lexer.next();
+
+	assert_eq!(separator.kind, TokenKind::Separator);
+	assert_eq!(paren.kind, TokenKind::LParen);
+	assert!(lexer.take_docs(number.span).is_empty());
+	assert!(lexer.take_docs(paren.span).is_empty());
+}
+
+#[test]
+fn string_escapes() {
+	let source = r#"s = "a\tb\n\"c\" \\ \u{1F600} é""#;
+
+	let expected = vec![
+		ident("s"),
+		TokenKind::Equal,
+		TokenKind::String(String::from("a\tb\n\"c\" \\ 😀 é")),
+	];
+
+	assert_eq!(kinds(source), expected);
+}
+
+#[test]
+fn string_errors() {
+	let cases = [
+		(r#""abc"#, "unterminated string", 0..4),
+		("\"abc\nx\"", "unterminated string", 0..4),
+		(r#""a\qb""#, "invalid escape sequence", 2..4),
+		(r#""\u{110000}""#, "invalid unicode escape", 1..11),
+		(r#""\u{}""#, "invalid unicode escape", 1..5),
+		(r#""\u0041""#, "invalid unicode escape", 1..3),
+	];
+
+	for (source, title, range) in cases {
+		let err = first_error(source);
+
+		assert_eq!(err.title, title, "{source}");
+		assert_eq!(err.labels[0].1, range, "{source}");
+	}
+}
+
+#[test]
+fn char_literals_and_primes() {
+	let source = r"x' = f('a', '\'', '\u{1F600}', x'')";
+
+	let expected = vec![
+		ident("x'"),
+		TokenKind::Equal,
+		ident("f"),
+		TokenKind::LParen,
+		TokenKind::Char('a'),
+		TokenKind::Comma,
+		TokenKind::Char('\''),
+		TokenKind::Comma,
+		TokenKind::Char('😀'),
+		TokenKind::Comma,
+		ident("x''"),
+		TokenKind::RParen,
+	];
+
+	assert_eq!(kinds(source), expected);
+}
+
+#[test]
+fn char_errors() {
+	let cases = [
+		("''", "empty character literal", 0..2),
+		("'ab'", "character literal with multiple characters", 0..4),
+		("'a\n'", "unterminated character literal", 0..2),
+		(r"'\q'", "invalid escape sequence", 1..3),
+	];
+
+	for (source, title, range) in cases {
+		let err = first_error(source);
+
+		assert_eq!(err.title, title, "{source}");
+		assert_eq!(err.labels[0].1, range, "{source}");
+	}
+}
+
+#[test]
+fn spans_are_byte_offsets() {
+	let source = "f() = \"é\" + x\ng() = 'λ' ++ \"→ ü\"";
+
+	let mut lexer = Lexer::new(Reader::new(source));
+
+	let lexemes: Vec<_> = tokens(&mut lexer)
+		.into_iter()
+		.filter(|token| token.span.0 != token.span.1)
+		.map(|token| (&source[Range::from(token.span)], Range::from(token.span)))
+		.collect();
+
+	let expected = vec![
+		("f", 0..1),
+		("(", 1..2),
+		(")", 2..3),
+		("=", 4..5),
+		("\"é\"", 6..10),
+		("+", 11..12),
+		("x", 13..14),
+		("g", 15..16),
+		("(", 16..17),
+		(")", 17..18),
+		("=", 19..20),
+		("'λ'", 21..25),
+		("++", 26..28),
+		("\"→ ü\"", 29..37),
+	];
+
+	assert_eq!(lexemes, expected);
+}
+
+#[test]
+fn float_literals() {
+	let source = "f(2.5, 1.5e-3, 2E10, 7e+2, 42)";
+
+	let expected = vec![
+		ident("f"),
+		TokenKind::LParen,
+		TokenKind::Float(2.5),
+		TokenKind::Comma,
+		TokenKind::Float(1.5e-3),
+		TokenKind::Comma,
+		TokenKind::Float(2E10),
+		TokenKind::Comma,
+		TokenKind::Float(7e+2),
+		TokenKind::Comma,
+		TokenKind::Integer(42),
+		TokenKind::RParen,
+	];
+
+	assert_eq!(kinds(source), expected);
+}
+
+#[test]
+fn malformed_floats() {
+	for (source, range) in [
+		("1.", 0..2),
+		("1.x", 0..2),
+		("1e", 0..2),
+		("1e)", 0..2),
+		("2.5e-", 0..5),
+	] {
+		let err = first_error(source);
+
+		assert_eq!(err.title, "malformed float literal", "{source}");
+		assert_eq!(err.labels[0].1, range, "{source}");
+	}
+}
+
+#[test]
+fn float_overflow() {
+	assert_eq!(kinds("1e308"), vec![TokenKind::Float(1e308)]);
+	assert_eq!(kinds("1e-999"), vec![TokenKind::Float(0.0)]);
+
+	for (source, range) in [("1e999", 0..5), ("1_0.0e3_08", 0..10)] {
+		let err = first_error(source);
+
+		assert_eq!(err.title, "float literal too large", "{source}");
+		assert_eq!(err.labels[0].1, range, "{source}");
+	}
+}
+
+#[test]
+fn radix_literals_and_separators() {
+	let source = "f(0xFF, 0o755, 0b1010, 1_000_000, 0x_dead_BEEF, 1_0.2_5, 0)";
+
+	let expected = vec![
+		ident("f"),
+		TokenKind::LParen,
+		TokenKind::Integer(0xFF),
+		TokenKind::Comma,
+		TokenKind::Integer(0o755),
+		TokenKind::Comma,
+		TokenKind::Integer(0b1010),
+		TokenKind::Comma,
+		TokenKind::Integer(1_000_000),
+		TokenKind::Comma,
+		TokenKind::Integer(0xdead_beef),
+		TokenKind::Comma,
+		TokenKind::Float(10.25),
+		TokenKind::Comma,
+		TokenKind::Integer(0),
+		TokenKind::RParen,
+	];
+
+	assert_eq!(kinds(source), expected);
+}
+
+#[test]
+fn radix_errors() {
+	let cases = [
+		("0b12", "invalid digit", 3..4),
+		("0o8", "invalid digit", 2..3),
+		("0xFG", "invalid digit", 3..4),
+		("0x", "missing digits", 0..2),
+		("0b_", "missing digits", 0..3),
+	];
+
+	for (source, title, range) in cases {
+		let err = first_error(source);
+
+		assert_eq!(err.title, title, "{source}");
+		assert_eq!(err.labels[0].1, range, "{source}");
+	}
+}
+
+#[test]
+fn integer_overflow() {
+	let cases = [
+		("9223372036854775807", Ok(TokenKind::Integer(i64::MAX))),
+		("9223372036854775808", Err(0..19)),
+		("99999999999999999999", Err(0..20)),
+		("0xFFFF_FFFF_FFFF_FFFF", Err(0..21)),
+		(
+			"0b1_0000000000000000000000000000000000000000000000000000000000000000",
+			Err(0..68),
+		),
+	];
+
+	for (source, expected) in cases {
+		match expected {
+			Ok(kind) => assert_eq!(kinds(source), vec![kind]),
+			Err(range) => {
+				let err = first_error(source);
+
+				assert_eq!(err.title, "integer literal too large", "{source}");
+				assert_eq!(err.labels[0].1, range, "{source}");
+			},
+		}
+	}
+}
+
+#[test]
+fn identifiers_cannot_start_with_digits() {
+	let cases = [
+		("2x", "did you mean `2 * x`?", 0..2),
+		("1abc", "did you mean `1 * abc`?", 0..4),
+		("1.5y'", "did you mean `1.5 * y'`?", 0..5),
+		("2_x", "did you mean `2 * x`?", 0..3),
+		("1_000__k", "did you mean `1_000 * k`?", 0..8),
+		("2exp", "did you mean `2 * exp`?", 0..4),
+		("3Eps", "did you mean `3 * Eps`?", 0..4),
+		("1.5e_3", "did you mean `1.5 * e_3`?", 0..6),
+		("2e3x", "did you mean `2e3 * x`?", 0..4),
+		("2!", "invalid numeric literal", 0..2),
+		("f(2?)", "invalid numeric literal", 2..4),
+		("2'", "invalid numeric literal", 0..2),
+	];
+
+	for (source, msg, range) in cases {
+		let err = first_error(source);
+
+		assert_eq!(err.title, "invalid numeric literal", "{source}");
+		assert_eq!(err.labels[0], (String::from(msg), range), "{source}");
+	}
+
+	assert_eq!(kinds("x2 _1"), vec![ident("x2"), ident("_1")]);
+}
+
+#[test]
+fn operators() {
+	let ops = [
+		"||", "&&", "==", "!=", "<", "<=", ">", ">=", "+", "-", "*", "/", "%", "**", "^", "~", ".",
+		"@", "$", "?", "<>", "|>", "&&&",
+	];
+
+	for op in ops {
+		let source = format!("a {op} b");
+		let expected = vec![
+			ident("a"),
+			TokenKind::Operator(String::from(op)),
+			ident("b"),
+		];
+
+		assert_eq!(kinds(&source), expected, "{op}");
+	}
+
+	let expected = vec![
+		ident("a"),
+		TokenKind::Operator(String::from("&&")),
+		ident("b"),
+		TokenKind::Operator(String::from("||")),
+		ident("c"),
+		TokenKind::Operator(String::from("%")),
+		TokenKind::Integer(2),
+	];
+
+	assert_eq!(kinds("a&&b||c%2"), expected);
+}
+
+#[test]
+fn operators_after_bang_and_question_mark() {
+	let op = |op: &str| TokenKind::Operator(String::from(op));
+
+	let cases = [
+		("x!=y", vec![ident("x"), op("!="), ident("y")]),
+		("x!= y", vec![ident("x"), op("!="), ident("y")]),
+		("x?<>y", vec![ident("x"), op("?<>"), ident("y")]),
+		("done? && ok!", vec![ident("done?"), op("&&"), ident("ok!")]),
+		("a?:b", vec![ident("a?"), TokenKind::Colon, ident("b")]),
+		(
+			"f!(x)",
+			vec![
+				ident("f!"),
+				TokenKind::LParen,
+				ident("x"),
+				TokenKind::RParen,
+			],
+		),
+		("x! = y", vec![ident("x!"), TokenKind::Equal, ident("y")]),
+	];
+
+	for (source, expected) in cases {
+		assert_eq!(kinds(source), expected, "{source}");
+	}
+}
+
+#[test]
+fn errors_become_tokens() {
+	let source = indoc! {"
+		f() =
+		  a ` b
+		   c
+		  \"d"
+	};
+
+	let mut lexer = Lexer::new(Reader::new(source));
+
+	let kinds: Vec<_> = tokens(&mut lexer)
+		.into_iter()
+		.map(|token| token.kind)
+		.collect();
+
+	let expected = vec![
+		ident("f"),
+		TokenKind::LParen,
+		TokenKind::RParen,
+		TokenKind::Equal,
+		TokenKind::BlockStart,
+		ident("a"),
+		TokenKind::Error,
+		ident("b"),
+		ident("c"),
+		TokenKind::Separator,
+		TokenKind::Error,
+		TokenKind::BlockEnd,
+	];
+
+	assert_eq!(kinds, expected);
+
+	let titles: Vec<_> = lexer
+		.take_errors()
+		.into_iter()
+		.map(|err| err.title)
+		.collect();
+	assert_eq!(
+		titles,
+		vec!["unrecognized character", "unterminated string"]
+	);
+}
+
+#[test]
+fn tokenize_yields_errors_in_place() {
+	let tokens: Vec<_> = crate::tokenize("f() = 1 ` 2")
+		.map(|result| result.map(|token| (token.kind, Range::from(token.span))))
+		.map(|result| result.map_err(|err| err.title))
+		.collect();
+
+	let expected = vec![
+		Ok((ident("f"), 0..1)),
+		Ok((TokenKind::LParen, 1..2)),
+		Ok((TokenKind::RParen, 2..3)),
+		Ok((TokenKind::Equal, 4..5)),
+		Ok((TokenKind::Integer(1), 6..7)),
+		Err(String::from("unrecognized character")),
+		Ok((TokenKind::Integer(2), 10..11)),
+	];
+
+	assert_eq!(tokens, expected);
+}
@@ -1,16 +1,26 @@
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::common::span::Span;
 
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum TokenKind {
 	Eof,
 	Boolean(bool),
 	Integer(i64),
+	Float(f64),
+	String(String),
+	Char(char),
 	Identifier(String),
 	KwIf,
 	KwThen,
 	KwElse,
 	KwWhile,
 	KwDo,
+	KwInfixl,
+	KwInfixr,
 	LParen,
 	RParen,
 	Comma,
@@ -18,9 +28,17 @@ pub enum TokenKind {
 	Equal,
 	RArrow,
 	Operator(String),
+	/// Synthetic layout token emitted between two lines at the same indent level.
 	Separator,
+	/// Synthetic layout token emitted when a line after `=`, `->`, `if`, `then`,
+	/// `else`, `while` or `do` is indented further than the current block.
 	BlockStart,
+	/// Synthetic layout token emitted for every block closed by a dedent or by
+	/// the end of the file.
 	BlockEnd,
+	/// Placeholder for input the lexer could not tokenize. The diagnostic is
+	/// reported separately, so the token stream stays well-formed.
+	Error,
 }
 
 impl TokenKind {
@@ -29,12 +47,17 @@ impl TokenKind {
 			TokenKind::Eof => String::from("end of file"),
 			TokenKind::Boolean(b) => format!("boolean ({b})"),
 			TokenKind::Integer(i) => format!("integer ({i})"),
+			TokenKind::Float(f) => format!("float ({f:?})"),
+			TokenKind::String(s) => format!("string ({s:?})"),
+			TokenKind::Char(c) => format!("character ({c:?})"),
 			TokenKind::Identifier(i) => format!("identifier ({i})"),
 			TokenKind::KwIf => String::from("keyword (if)"),
 			TokenKind::KwThen => String::from("keyword (then)"),
 			TokenKind::KwElse => String::from("keyword (else)"),
 			TokenKind::KwWhile => String::from("keyword (while)"),
 			TokenKind::KwDo => String::from("keyword (do)"),
+			TokenKind::KwInfixl => String::from("keyword (infixl)"),
+			TokenKind::KwInfixr => String::from("keyword (infixr)"),
 			TokenKind::LParen => String::from("left parenthesis"),
 			TokenKind::RParen => String::from("right parenthesis"),
 			TokenKind::Comma => String::from("comma"),
@@ -45,11 +68,13 @@ impl TokenKind {
 			TokenKind::Separator => String::from("newline"),
 			TokenKind::BlockStart => String::from("start of block"),
 			TokenKind::BlockEnd => String::from("end of block"),
+			TokenKind::Error => String::from("invalid token"),
 		}
 	}
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Token {
 	pub kind: TokenKind,
 	pub span: Span,
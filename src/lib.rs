@@ -1,13 +1,73 @@
 pub mod common;
+pub mod format;
 pub mod parser;
 
 mod lexer;
 mod reader;
 
+use rosy_error::RosyError;
+
 use crate::common::error::R;
+use crate::format::format_cst;
+use crate::lexer::Lexer;
+use crate::parser::arena::ArenaAst;
 use crate::parser::ast::Ast;
+use crate::parser::cst::{SyntaxMap, SyntaxNode};
+use crate::parser::ids::NodeMap;
 use crate::parser::Parser;
+use crate::reader::Reader;
+
+pub use crate::lexer::token::{Token, TokenKind};
 
 pub fn parse<'a>(source: &'a str) -> R<Ast> {
 	Parser::new(source).parse()
 }
+
+/// Parses `source` like [`parse`], additionally returning a [`NodeMap`] that
+/// finds any node of the tree by its [`NodeId`](crate::parser::ast::NodeId).
+pub fn parse_with_nodes(source: &str) -> R<(Ast, NodeMap)> {
+	Parser::new(source).parse_with_nodes()
+}
+
+pub fn parse_recovering(source: &str) -> (Ast, Vec<RosyError>) {
+	Parser::new(source).parse_recovering()
+}
+
+/// Parses `source` like [`parse_recovering`], additionally returning a lossless
+/// concrete syntax tree and a map from each AST node to its part of that tree.
+/// Whitespace and comments are kept as trivia tokens, so the tree always prints
+/// back to `source`, even when it contains errors.
+pub fn parse_with_cst(source: &str) -> (Ast, SyntaxNode, SyntaxMap, Vec<RosyError>) {
+	Parser::new(source).parse_with_cst()
+}
+
+/// Parses `source` like [`parse_recovering`], keeping expressions and
+/// statements in a per-file arena instead of boxing every node, which is
+/// cheaper when parsing many files. Arena nodes are identified by their
+/// [`ExprId`](crate::parser::arena::ExprId) and
+/// [`StmtId`](crate::parser::arena::StmtId) and carry no `NodeId`;
+/// [`ArenaAst::into_ast`] numbers them.
+pub fn parse_arena(source: &str) -> (ArenaAst, Vec<RosyError>) {
+	Parser::new_arena(source).parse_arena()
+}
+
+/// Parses `source` and renders it back as canonical Rosy source, keeping its
+/// comments.
+pub fn format_source(source: &str) -> R<String> {
+	let (ast, cst, _, errors) = parse_with_cst(source);
+
+	match errors.into_iter().next() {
+		Some(err) => Err(err),
+		None => format_cst(&ast, &cst),
+	}
+}
+
+/// Lexes `source` into tokens, ending before the end of file. Lexing continues
+/// after an error, which is yielded in place of the offending token.
+///
+/// Besides tokens for the source text, the stream contains the synthetic
+/// layout tokens [`TokenKind::Separator`], [`TokenKind::BlockStart`] and
+/// [`TokenKind::BlockEnd`], which have empty spans.
+pub fn tokenize(source: &str) -> impl Iterator<Item = R<Token>> + '_ {
+	Lexer::new(Reader::new(source))
+}
@@ -1,118 +1,264 @@
+pub mod arena;
 pub mod ast;
+pub mod cst;
+pub mod fold;
+pub mod ids;
+pub mod lower;
+pub mod tree;
+pub mod visit;
+
+use std::collections::HashMap;
+use std::mem;
+
+use rosy_error::RosyError;
+
+#[cfg(test)]
+mod tests;
 
 use crate::common::error::parser::*;
 use crate::common::error::R;
 use crate::common::span::Span;
 use crate::lexer::token::{Token, TokenKind};
 use crate::lexer::Lexer;
+use crate::parser::arena::{Arena, ArenaAst};
 use crate::parser::ast::*;
+use crate::parser::cst::{syntax_map, CstBuilder, NodeKind, SyntaxMap, SyntaxNode};
+use crate::parser::ids::NodeMap;
+use crate::parser::tree::{Boxed, Leaf, TreeBuilder};
 use crate::reader::Reader;
 
-pub struct Parser {
-	lexer: Lexer,
+/// Parses source into the tree built by `T`, by default the boxed [`Ast`].
+pub struct Parser<'a, T: TreeBuilder = Boxed> {
+	source: &'a str,
+	lexer: Lexer<'a>,
+	fixities: HashMap<String, (OpAssoc, usize)>,
+	errors: Vec<RosyError>,
+	cst: Option<CstBuilder<'a>>,
+	tree: T,
+	// arguments and statements are collected here until their list is complete,
+	// so nested lists can be parsed without allocating
+	expr_stack: Vec<T::Expr>,
+	stmt_stack: Vec<T::Stmt>,
 }
 
-impl Parser {
-	pub fn new(lexer: &str) -> Self {
-		let reader = Reader::new(lexer);
+impl<'a> Parser<'a> {
+	pub fn new(source: &'a str) -> Self {
+		Parser::with_tree(source)
+	}
+
+	pub fn parse(&mut self) -> R<Ast> {
+		let (ast, mut errors) = self.parse_recovering();
+
+		if errors.is_empty() {
+			Ok(ast)
+		} else {
+			Err(errors.remove(0))
+		}
+	}
+
+	pub fn parse_recovering(&mut self) -> (Ast, Vec<RosyError>) {
+		let (ast, _, errors) = self.parse_passes();
+
+		(ast, errors)
+	}
+
+	/// Parses like [`Parser::parse`], additionally returning a [`NodeMap`] to
+	/// look nodes up by their [`NodeId`].
+	pub fn parse_with_nodes(&mut self) -> R<(Ast, NodeMap)> {
+		let ast = self.parse()?;
+		let nodes = NodeMap::new(&ast);
+
+		Ok((ast, nodes))
+	}
+
+	/// Parses like [`Parser::parse_recovering`], additionally building a
+	/// lossless concrete syntax tree whose text is exactly the source, and a
+	/// map from each AST node to its part of that tree.
+	pub fn parse_with_cst(&mut self) -> (Ast, SyntaxNode, SyntaxMap, Vec<RosyError>) {
+		self.cst = Some(CstBuilder::new(self.source));
+
+		let (ast, cst, errors) = self.parse_passes();
+		let cst = cst.unwrap();
+		let nodes = syntax_map(&ast, &cst);
+
+		(ast, cst, nodes, errors)
+	}
+}
+
+impl<'a> Parser<'a, Arena> {
+	pub fn new_arena(source: &'a str) -> Self {
+		Parser::with_tree(source)
+	}
+
+	/// Parses like [`Parser::parse_recovering`], keeping expressions and
+	/// statements in a per-file arena.
+	pub fn parse_arena(&mut self) -> (ArenaAst, Vec<RosyError>) {
+		let (ast, _, errors) = self.parse_passes();
+
+		(ast, errors)
+	}
+}
+
+impl<'a, T: TreeBuilder> Parser<'a, T> {
+	fn with_tree(source: &'a str) -> Self {
+		let reader = Reader::new(source);
 		let lexer = Lexer::new(reader);
 
-		Self { lexer }
+		Self {
+			source,
+			lexer,
+			fixities: HashMap::new(),
+			errors: Vec::new(),
+			cst: None,
+			tree: T::default(),
+			expr_stack: Vec::new(),
+			stmt_stack: Vec::new(),
+		}
 	}
 
-	pub fn parse(&mut self) -> R<Ast> {
-		let mut ast = Vec::new();
+	fn parse_passes(&mut self) -> (T::Output, Option<SyntaxNode>, Vec<RosyError>) {
+		let decls = self.parse_decls();
+		let fixities = collect_fixities(decls.iter().filter_map(T::as_fixity_decl));
+
+		if fixities.is_empty() {
+			let ast = mem::take(&mut self.tree).finish(decls);
+			let cst = self.cst.take().map(CstBuilder::finish);
+
+			return (ast, cst, self.take_errors());
+		}
+
+		// operators may be used before their fixity is declared, so the first
+		// pass only collects declarations and the second pass applies them
+		let mut parser = Parser::<T>::with_tree(self.source);
+		parser.fixities = fixities;
+		parser.cst = self.cst.take().map(|_| CstBuilder::new(self.source));
+
+		let decls = parser.parse_decls();
+		let ast = mem::take(&mut parser.tree).finish(decls);
+		let cst = parser.cst.take().map(CstBuilder::finish);
+
+		(ast, cst, parser.take_errors())
+	}
+
+	fn take_errors(&mut self) -> Vec<RosyError> {
+		let mut errors = self.lexer.take_errors();
+		errors.append(&mut self.errors);
+
+		errors.sort_by_key(|err| err.labels.first().map(|(_, range)| range.start));
+
+		errors
+	}
+
+	fn parse_decls(&mut self) -> Vec<T::Decl> {
+		let mut decls = Vec::new();
 
 		while !self.lexer.eof() {
-			let decl = self.parse_decl()?;
-			ast.push(decl);
+			let start = self.span();
+			let checkpoint = self.checkpoint();
+			let depth = self.node_depth();
+
+			let decl = match self.parse_decl() {
+				Ok(decl) => decl,
+				Err(err) => {
+					self.unwind(depth);
+					self.start_node_at(checkpoint, NodeKind::Error);
+
+					let span = self.recover(err, start, false);
+					self.finish_node();
+
+					self.tree.error_decl(span)
+				},
+			};
 
-			self.discard(TokenKind::Separator)?;
+			decls.push(decl);
+
+			self.discard(TokenKind::Separator);
 		}
 
-		Ok(ast)
+		decls
 	}
 
-	fn parse_decl(&mut self) -> R<Decl> {
+	fn parse_decl(&mut self) -> R<T::Decl> {
+		if matches!(self.kind(), TokenKind::KwInfixl | TokenKind::KwInfixr) {
+			let fixity_decl = self.parse_fixity_decl()?;
+
+			return Ok(self.tree.fixity_decl(fixity_decl));
+		}
+
+		let checkpoint = self.checkpoint();
 		let identifier = self.parse_identifier()?;
-		let span = self.span()?;
+		let span = self.span();
 
-		match self.kind()? {
+		match self.kind() {
 			TokenKind::LParen => {
-				let fn_def = self.parse_fn_def(identifier)?;
-				let span = fn_def.span;
+				self.start_node_at(checkpoint, NodeKind::FnDef);
 
-				let decl = Decl {
-					kind: DeclKind::Fn(fn_def),
-					span,
-				};
+				let decl = self.parse_fn_def(identifier)?;
+				self.finish_node();
 
 				Ok(decl)
 			},
 			TokenKind::Colon => {
-				let ty_decl = self.parse_ty_decl(identifier)?;
-				let span = ty_decl.span;
+				self.start_node_at(checkpoint, NodeKind::TyDecl);
 
-				let decl = Decl {
-					kind: DeclKind::Ty(ty_decl),
-					span,
-				};
+				let ty_decl = self.parse_ty_decl(identifier)?;
+				self.finish_node();
 
-				Ok(decl)
+				Ok(self.tree.ty_decl(ty_decl))
 			},
 			kind => Err(invalid_declaration(kind, identifier.span, span)),
 		}
 	}
 
-	fn parse_stmt(&mut self) -> R<Stmt> {
-		let stmt = if matches!(self.kind()?, TokenKind::Identifier(_)) {
+	fn parse_stmt(&mut self) -> R<T::Stmt> {
+		let checkpoint = self.checkpoint();
+
+		let stmt = if matches!(self.lexer.peek().kind, TokenKind::Identifier(_)) {
 			let identifier = self.parse_identifier()?;
 
+			// only type declarations take docs inside a block
+			if !self.kind_is(TokenKind::Colon) {
+				self.lexer.take_docs(identifier.span);
+			}
+
 			if self.kind_is(TokenKind::Colon) {
+				self.start_node_at(checkpoint, NodeKind::TyDecl);
+
 				let ty_decl = self.parse_ty_decl(identifier)?;
-				let span = ty_decl.span;
+				self.finish_node();
 
-				Stmt {
-					kind: StmtKind::TyDecl(ty_decl),
-					span,
-				}
+				self.tree.ty_decl_stmt(ty_decl)
 			} else if self.kind_is(TokenKind::Equal) {
-				let var_def = self.parse_var_def(identifier)?;
-				let span = var_def.span;
+				self.start_node_at(checkpoint, NodeKind::VarDef);
 
-				Stmt {
-					kind: StmtKind::VarDef(var_def),
-					span,
-				}
+				let stmt = self.parse_var_def(identifier)?;
+				self.finish_node();
+
+				stmt
 			} else {
-				let span = identifier.span;
+				self.start_node_at(checkpoint, NodeKind::Name);
+				self.finish_node();
 
-				let expr = Expr {
-					kind: ExprKind::Identifier(identifier.name),
-					span,
-				};
+				let span = identifier.span;
 
-				let expr = self.parse_infix(expr, 0)?;
+				let expr = self.tree.leaf(Leaf::Identifier(identifier.name), span);
+				let expr = self.parse_infix(expr, 0, checkpoint)?;
 
-				Stmt {
-					kind: StmtKind::Expr(expr),
-					span,
-				}
+				self.tree.expr_stmt(expr, span)
 			}
 		} else {
 			let expr = self.parse_expr(0)?;
-			let span = expr.span;
+			let span = self.tree.span(&expr);
 
-			Stmt {
-				kind: StmtKind::Expr(expr),
-				span,
-			}
+			self.tree.expr_stmt(expr, span)
 		};
 
 		Ok(stmt)
 	}
 
 	fn parse_ty_decl(&mut self, identifier: Identifier) -> R<TyDecl> {
+		let docs = self.lexer.take_docs(identifier.span);
+
 		self.consume(TokenKind::Colon)?;
 
 		let ty = self.parse_ty()?;
@@ -123,13 +269,17 @@ impl Parser {
 		let ty_decl = TyDecl {
 			identifier,
 			ty,
+			docs,
 			span: Span::between(start, end),
 		};
 
 		Ok(ty_decl)
 	}
 
-	fn parse_fn_def(&mut self, func: Identifier) -> R<FnDef> {
+	fn parse_fn_def(&mut self, func: Identifier) -> R<T::Decl> {
+		let docs = self.lexer.take_docs(func.span);
+
+		self.start_node(NodeKind::ParamList);
 		self.consume(TokenKind::LParen)?;
 
 		let mut params = Vec::new();
@@ -138,76 +288,140 @@ impl Parser {
 			let identifier = self.parse_identifier()?;
 			params.push(identifier);
 
-			self.discard(TokenKind::Comma)?;
+			self.discard(TokenKind::Comma);
 		}
 
 		self.consume(TokenKind::RParen)?;
+		self.finish_node();
+
 		self.consume(TokenKind::Equal)?;
 
 		let expr = self.parse_expr(0)?;
 
 		let start = func.span;
-		let end = expr.span;
+		let end = self.tree.span(&expr);
+
+		let span = Span::between(start, end);
+
+		Ok(self.tree.fn_def(func, params, expr, docs, span))
+	}
+
+	fn parse_fixity_decl(&mut self) -> R<FixityDecl> {
+		self.start_node(NodeKind::FixityDecl);
+
+		let token = self.bump();
+		let start = token.span;
+
+		let docs = self.lexer.take_docs(start);
+
+		let assoc = match token.kind {
+			TokenKind::KwInfixl => OpAssoc::Left,
+			TokenKind::KwInfixr => OpAssoc::Right,
+			_ => unreachable!(),
+		};
 
-		let fn_def = FnDef {
-			func,
-			params,
-			body: expr,
+		let token = self.bump();
+
+		let prec = match token.kind {
+			TokenKind::Integer(i) if (MIN_PREC as i64..=MAX_PREC as i64).contains(&i) => i as usize,
+			kind => return Err(invalid_precedence(&kind, token.span)),
+		};
+
+		let mut ops = vec![self.parse_operator()?];
+
+		while matches!(self.kind(), TokenKind::Operator(_)) {
+			ops.push(self.parse_operator()?);
+		}
+
+		let end = ops[ops.len() - 1].span;
+
+		self.finish_node();
+
+		let fixity_decl = FixityDecl {
+			assoc,
+			prec,
+			ops,
+			docs,
 			span: Span::between(start, end),
 		};
 
-		Ok(fn_def)
+		Ok(fixity_decl)
 	}
 
-	fn parse_var_def(&mut self, identifier: Identifier) -> R<VarDef> {
+	fn parse_var_def(&mut self, identifier: Identifier) -> R<T::Stmt> {
 		self.consume(TokenKind::Equal)?;
 
 		let expr = self.parse_expr(0)?;
 
 		let start = identifier.span;
-		let end = expr.span;
+		let end = self.tree.span(&expr);
 
-		let var_def = VarDef {
-			var: identifier,
-			value: expr,
-			span: Span::between(start, end),
-		};
+		let span = Span::between(start, end);
 
-		Ok(var_def)
+		Ok(self.tree.var_def(identifier, expr, span))
 	}
 
-	fn parse_expr(&mut self, prec: usize) -> R<Expr> {
-		let start = self.span()?;
+	fn parse_expr(&mut self, prec: usize) -> R<T::Expr> {
+		let checkpoint = self.checkpoint();
+
+		if self.lexer.peek().kind == TokenKind::Error {
+			let token = self.bump_node(NodeKind::Error);
+
+			let expr = self.tree.leaf(Leaf::Error, token.span);
+
+			return self.parse_infix(expr, prec, checkpoint);
+		}
+
+		let start = self.span();
 
-		let lhs = match self.kind()? {
+		let lhs = match self.kind() {
 			TokenKind::Boolean(b) => {
-				let kind = ExprKind::Boolean(*b);
-				self.lexer.next()?;
+				let leaf = Leaf::Boolean(*b);
+				self.bump_node(NodeKind::Literal);
 
-				Expr { kind, span: start }
+				self.tree.leaf(leaf, start)
 			},
 			TokenKind::Integer(i) => {
-				let kind = ExprKind::Integer(*i);
-				self.lexer.next()?;
+				let leaf = Leaf::Integer(*i);
+				self.bump_node(NodeKind::Literal);
+
+				self.tree.leaf(leaf, start)
+			},
+			TokenKind::Float(f) => {
+				let leaf = Leaf::Float(*f);
+				self.bump_node(NodeKind::Literal);
 
-				Expr { kind, span: start }
+				self.tree.leaf(leaf, start)
+			},
+			TokenKind::String(s) => {
+				let leaf = Leaf::String(s.clone());
+				self.bump_node(NodeKind::Literal);
+
+				self.tree.leaf(leaf, start)
+			},
+			TokenKind::Char(c) => {
+				let leaf = Leaf::Char(*c);
+				self.bump_node(NodeKind::Literal);
+
+				self.tree.leaf(leaf, start)
 			},
 			TokenKind::Identifier(i) => {
-				let kind = ExprKind::Identifier(i.clone());
-				self.lexer.next()?;
+				let leaf = Leaf::Identifier(i.clone());
+				self.bump_node(NodeKind::Name);
 
-				Expr { kind, span: start }
+				self.tree.leaf(leaf, start)
 			},
 			TokenKind::KwIf => {
+				self.start_node(NodeKind::If);
 				self.consume(TokenKind::KwIf)?;
 
 				let cond = self.parse_expr(0)?;
 
-				self.discard(TokenKind::Separator)?;
+				self.discard(TokenKind::Separator);
 				self.consume(TokenKind::KwThen)?;
 
 				let then_expr = self.parse_expr(0)?;
-				let mut end = then_expr.span;
+				let mut end = self.tree.span(&then_expr);
 
 				if self.kind_is(TokenKind::Separator) {
 					let sep = self.consume(TokenKind::Separator)?;
@@ -221,121 +435,125 @@ impl Parser {
 					self.consume(TokenKind::KwElse)?;
 
 					let else_expr = self.parse_expr(0)?;
-					end = else_expr.span;
+					end = self.tree.span(&else_expr);
 
-					Some(Box::new(else_expr))
+					Some(else_expr)
 				} else {
 					None
 				};
 
-				Expr {
-					kind: ExprKind::If(Box::new(cond), Box::new(then_expr), else_expr),
-					span: Span::between(start, end),
-				}
+				self.finish_node();
+
+				let span = Span::between(start, end);
+
+				self.tree.if_expr(cond, then_expr, else_expr, span)
 			},
 			TokenKind::KwWhile => {
+				self.start_node(NodeKind::While);
 				self.consume(TokenKind::KwWhile)?;
 
 				let cond = self.parse_expr(0)?;
 
-				self.discard(TokenKind::Separator)?;
+				self.discard(TokenKind::Separator);
 				self.consume(TokenKind::KwDo)?;
 
 				let expr = self.parse_expr(0)?;
-				let end = expr.span;
+				let end = self.tree.span(&expr);
 
-				Expr {
-					kind: ExprKind::While(Box::new(cond), Box::new(expr)),
-					span: Span::between(start, end),
-				}
+				self.finish_node();
+
+				let span = Span::between(start, end);
+
+				self.tree.while_expr(cond, expr, span)
 			},
 			TokenKind::LParen => {
+				self.start_node(NodeKind::Paren);
 				self.consume(TokenKind::LParen)?;
 
-				let expr = self.parse_expr(0)?;
+				let mut expr = self.parse_expr(0)?;
 
 				let token = self.consume(TokenKind::RParen)?;
 				let end = token.span;
 
-				Expr {
-					kind: expr.kind,
-					span: Span::between(start, end),
-				}
+				self.finish_node();
+
+				self.tree.set_span(&mut expr, Span::between(start, end));
+
+				expr
 			},
 			TokenKind::Operator(_) => {
-				let token = self.lexer.next()?;
+				self.start_node(NodeKind::Unary);
+
+				let token = self.bump();
 
 				let TokenKind::Operator(op) = token.kind else {
 					unreachable!();
 				};
 
-				let func = Expr {
-					kind: ExprKind::Identifier(op),
+				let un_op = UnaryOp {
+					lexeme: op,
 					span: start,
 				};
 
-				let arg = self.parse_expr(0)?;
-				let end = arg.span;
+				let operand = self.parse_expr(UNARY_PREC)?;
+				let end = self.tree.span(&operand);
 
-				Expr {
-					kind: ExprKind::Call(Box::new(func), vec![arg]),
-					span: Span::between(start, end),
-				}
+				self.finish_node();
+
+				let span = Span::between(start, end);
+
+				self.tree.unary(un_op, operand, span)
 			},
 			TokenKind::BlockStart => {
+				self.start_node(NodeKind::Block);
 				self.consume(TokenKind::BlockStart)?;
 
-				let mut stmts = Vec::new();
+				let base = self.stmt_stack.len();
 
-				while !self.kind_is(TokenKind::BlockEnd) {
-					let stmt = self.parse_stmt()?;
-					stmts.push(stmt);
+				let end = self.parse_block_stmts().inspect_err(|_| {
+					self.stmt_stack.truncate(base);
+				})?;
 
-					self.discard(TokenKind::Separator)?;
-				}
+				self.finish_node();
 
-				let token = self.consume(TokenKind::BlockEnd)?;
-				let end = token.span;
+				let stmts = self.stmt_stack.drain(base..);
+				let span = Span::between(start, end);
 
-				Expr {
-					kind: ExprKind::Block(stmts),
-					span: Span::between(start, end),
-				}
+				self.tree.block(stmts, span)
 			},
 			kind => return Err(invalid_expr(kind, start)),
 		};
 
-		let expr = self.parse_infix(lhs, prec)?;
+		let expr = self.parse_infix(lhs, prec, checkpoint)?;
 
 		Ok(expr)
 	}
 
-	fn parse_infix(&mut self, mut lhs: Expr, prec: usize) -> R<Expr> {
-		let start = lhs.span;
+	fn parse_infix(&mut self, mut lhs: T::Expr, prec: usize, checkpoint: usize) -> R<T::Expr> {
+		let start = self.tree.span(&lhs);
 
 		loop {
-			let span = self.span()?;
+			let span = self.span();
 
-			match self.kind()? {
+			match self.kind() {
 				TokenKind::LParen => {
+					self.start_node_at(checkpoint, NodeKind::Call);
+					self.start_node(NodeKind::ArgList);
 					self.consume(TokenKind::LParen)?;
 
-					let mut args = Vec::new();
+					let base = self.expr_stack.len();
 
-					while !self.kind_is(TokenKind::RParen) {
-						let arg = self.parse_expr(0)?;
-						args.push(arg);
+					let end = self.parse_args().inspect_err(|_| {
+						self.expr_stack.truncate(base);
+					})?;
 
-						self.discard(TokenKind::Comma)?;
-					}
+					self.finish_node();
+					self.finish_node();
 
-					let token = self.consume(TokenKind::RParen)?;
-					let end = token.span;
+					let args = self.expr_stack.drain(base..);
+					let span = Span::between(start, end);
 
-					lhs = Expr {
-						kind: ExprKind::Call(Box::new(lhs), args),
-						span: Span::between(start, end),
-					};
+					lhs = self.tree.call(lhs, args, span);
 				},
 				TokenKind::Operator(op) => {
 					let bin_op = BinaryOp {
@@ -343,26 +561,25 @@ impl Parser {
 						span,
 					};
 
-					match bin_op.assoc() {
-						OpAssoc::Left if bin_op.prec() <= prec => break,
-						OpAssoc::Right if bin_op.prec() < prec => break,
+					let (op_assoc, op_prec) = self.fixity(&bin_op);
+
+					match op_assoc {
+						OpAssoc::Left if op_prec <= prec => break,
+						OpAssoc::Right if op_prec < prec => break,
 						_ => (),
 					}
 
-					self.lexer.next()?;
+					self.start_node_at(checkpoint, NodeKind::Binary);
+					self.bump();
 
-					let rhs = self.parse_expr(bin_op.prec())?;
-					let end = rhs.span;
+					let rhs = self.parse_expr(op_prec)?;
+					let end = self.tree.span(&rhs);
 
-					let func = Expr {
-						kind: ExprKind::Identifier(bin_op.lexeme),
-						span: bin_op.span,
-					};
+					self.finish_node();
 
-					lhs = Expr {
-						kind: ExprKind::Call(Box::new(func), vec![lhs, rhs]),
-						span: Span::between(start, end),
-					};
+					let span = Span::between(start, end);
+
+					lhs = self.tree.binary(bin_op, lhs, rhs, span);
 				},
 				TokenKind::Eof
 				| TokenKind::KwThen
@@ -379,17 +596,66 @@ impl Parser {
 		Ok(lhs)
 	}
 
+	// statements are pushed onto `stmt_stack`, which the caller truncates if
+	// the block fails to parse, so an enclosing block never sees them
+	fn parse_block_stmts(&mut self) -> R<Span> {
+		while !self.kind_is(TokenKind::BlockEnd) && !self.lexer.eof() {
+			let start = self.span();
+			let checkpoint = self.checkpoint();
+			let depth = self.node_depth();
+
+			let stmt = match self.parse_stmt() {
+				Ok(stmt) => stmt,
+				Err(err) => {
+					self.unwind(depth);
+					self.start_node_at(checkpoint, NodeKind::Error);
+
+					let span = self.recover(err, start, true);
+					self.finish_node();
+
+					let expr = self.tree.leaf(Leaf::Error, span);
+
+					self.tree.expr_stmt(expr, span)
+				},
+			};
+
+			self.stmt_stack.push(stmt);
+
+			self.discard(TokenKind::Separator);
+		}
+
+		let token = self.consume(TokenKind::BlockEnd)?;
+
+		Ok(token.span)
+	}
+
+	// likewise for arguments and `expr_stack`
+	fn parse_args(&mut self) -> R<Span> {
+		while !self.kind_is(TokenKind::RParen) {
+			let arg = self.parse_expr(0)?;
+			self.expr_stack.push(arg);
+
+			self.discard(TokenKind::Comma);
+		}
+
+		let token = self.consume(TokenKind::RParen)?;
+
+		Ok(token.span)
+	}
+
 	fn parse_ty(&mut self) -> R<Ty> {
-		let start = self.span()?;
+		let start = self.span();
+		let checkpoint = self.checkpoint();
 
-		let mut lhs = match self.kind()? {
+		let mut lhs = match self.kind() {
 			TokenKind::Identifier(i) => {
 				let kind = TyKind::Single(i.clone());
-				self.lexer.next()?;
+				self.bump_node(NodeKind::SingleTy);
 
 				Ty { kind, span: start }
 			},
 			TokenKind::LParen => {
+				self.start_node(NodeKind::TupleTy);
 				self.consume(TokenKind::LParen)?;
 
 				let mut tys = Vec::new();
@@ -398,12 +664,14 @@ impl Parser {
 					let ty = self.parse_ty()?;
 					tys.push(ty);
 
-					self.discard(TokenKind::Comma)?;
+					self.discard(TokenKind::Comma);
 				}
 
 				let token = self.consume(TokenKind::RParen)?;
 				let end = token.span;
 
+				self.finish_node();
+
 				Ty {
 					kind: TyKind::Tuple(tys),
 					span: Span::between(start, end),
@@ -413,11 +681,14 @@ impl Parser {
 		};
 
 		if self.kind_is(TokenKind::RArrow) {
+			self.start_node_at(checkpoint, NodeKind::FunctionTy);
 			self.consume(TokenKind::RArrow)?;
 
 			let rhs = self.parse_ty()?;
 			let end = rhs.span;
 
+			self.finish_node();
+
 			let kind = match lhs.kind {
 				TyKind::Tuple(tys) => TyKind::Function(tys, Box::new(rhs)),
 				_ => TyKind::Function(vec![lhs], Box::new(rhs)),
@@ -433,11 +704,12 @@ impl Parser {
 	}
 
 	fn parse_identifier(&mut self) -> R<Identifier> {
-		let token = self.lexer.next()?;
+		let token = self.bump();
 
 		match token.kind {
 			TokenKind::Identifier(name) => {
 				let identifier = Identifier {
+					id: NodeId::DUMMY,
 					name,
 					span: token.span,
 				};
@@ -448,37 +720,151 @@ impl Parser {
 		}
 	}
 
-	fn discard(&mut self, kind: TokenKind) -> R<()> {
-		if self.kind_is(kind) {
-			self.lexer.next()?;
+	fn parse_operator(&mut self) -> R<Identifier> {
+		let token = self.bump();
+
+		match token.kind {
+			TokenKind::Operator(name) => {
+				let identifier = Identifier {
+					id: NodeId::DUMMY,
+					name,
+					span: token.span,
+				};
+
+				Ok(identifier)
+			},
+			kind => Err(invalid_operator(&kind, token.span)),
 		}
+	}
+
+	fn fixity(&self, bin_op: &BinaryOp) -> (OpAssoc, usize) {
+		match self.fixities.get(&bin_op.lexeme) {
+			Some(fixity) => *fixity,
+			None => (bin_op.assoc(), bin_op.prec()),
+		}
+	}
+
+	fn recover(&mut self, err: RosyError, start: Span, in_block: bool) -> Span {
+		self.errors.push(err);
+
+		let mut depth = 0;
+
+		loop {
+			let is_boundary = match self.kind() {
+				TokenKind::Eof => true,
+				TokenKind::Separator => depth == 0,
+				TokenKind::BlockEnd => depth == 0 && in_block,
+				_ => false,
+			};
+
+			if is_boundary {
+				break;
+			}
+
+			match self.bump().kind {
+				TokenKind::BlockStart => depth += 1,
+				TokenKind::BlockEnd if depth > 0 => depth -= 1,
+				_ => (),
+			}
+		}
+
+		let end = self.lexer.prev_span();
+
+		if start.0 <= end.0 {
+			Span::between(start, end)
+		} else {
+			start
+		}
+	}
+
+	fn bump(&mut self) -> Token {
+		let token = self.lexer.next();
+
+		if let Some(cst) = &mut self.cst {
+			cst.token(&token);
+		}
+
+		token
+	}
+
+	fn bump_node(&mut self, kind: NodeKind) -> Token {
+		self.start_node(kind);
+		let token = self.bump();
+		self.finish_node();
 
-		Ok(())
+		token
+	}
+
+	fn start_node(&mut self, kind: NodeKind) {
+		if let Some(cst) = &mut self.cst {
+			cst.start_node(kind, self.lexer.peek().span.0);
+		}
+	}
+
+	fn start_node_at(&mut self, checkpoint: usize, kind: NodeKind) {
+		if let Some(cst) = &mut self.cst {
+			cst.start_node_at(checkpoint, kind);
+		}
+	}
+
+	fn finish_node(&mut self) {
+		if let Some(cst) = &mut self.cst {
+			cst.finish_node();
+		}
+	}
+
+	fn checkpoint(&mut self) -> usize {
+		match &mut self.cst {
+			Some(cst) => cst.checkpoint(self.lexer.peek().span.0),
+			None => 0,
+		}
+	}
+
+	fn node_depth(&self) -> usize {
+		self.cst.as_ref().map_or(0, CstBuilder::depth)
+	}
+
+	// nodes left open by a failed parse are closed before recovering
+	fn unwind(&mut self, depth: usize) {
+		while self.node_depth() > depth {
+			self.finish_node();
+		}
+	}
+
+	fn skip_errors(&mut self) {
+		while self.lexer.peek().kind == TokenKind::Error {
+			self.bump();
+		}
+	}
+
+	fn discard(&mut self, kind: TokenKind) {
+		if self.kind_is(kind) {
+			self.bump();
+		}
 	}
 
 	fn consume(&mut self, kind: TokenKind) -> R<Token> {
-		let span = self.span()?;
-		let actual_kind = self.kind()?;
+		let span = self.span();
+		let actual_kind = self.kind();
 
 		if *actual_kind == kind {
-			self.lexer.next()
+			Ok(self.bump())
 		} else {
 			Err(unexpected_token(actual_kind, &kind, span))
 		}
 	}
 
 	fn kind_is(&mut self, kind: TokenKind) -> bool {
-		match self.kind() {
-			Ok(k) => *k == kind,
-			Err(_) => false,
-		}
+		*self.kind() == kind
 	}
 
-	fn kind(&mut self) -> R<&TokenKind> {
-		self.lexer.peek().map(|t| &t.kind)
+	fn kind(&mut self) -> &TokenKind {
+		self.skip_errors();
+		&self.lexer.peek().kind
 	}
 
-	fn span(&mut self) -> R<Span> {
-		self.lexer.peek().map(|t| t.span)
+	fn span(&mut self) -> Span {
+		self.skip_errors();
+		self.lexer.peek().span
 	}
 }
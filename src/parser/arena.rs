@@ -0,0 +1,544 @@
+use std::ops::{Index, IndexMut};
+
+use crate::common::span::Span;
+use crate::parser::ast::{
+	self, BinaryOp, DocComment, FixityDecl, Identifier, NodeId, TyDecl, UnaryOp,
+};
+use crate::parser::tree::{Leaf, TreeBuilder};
+
+/// An AST whose expressions and statements live in one [`Arena`] per file and
+/// refer to each other by index, instead of each child being boxed.
+///
+/// Nodes here are identified by their index, so identifiers keep
+/// [`NodeId::DUMMY`]; [`ArenaAst::into_ast`] moves the nodes out into the boxed
+/// [`ast::Ast`], numbering them like the parser does.
+#[derive(Clone, Debug, Default)]
+pub struct ArenaAst {
+	pub decls: Vec<Decl>,
+	pub arena: Arena,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ExprId(pub u32);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct StmtId(pub u32);
+
+/// The arguments of a call, stored contiguously in the arena.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ExprList {
+	start: u32,
+	end: u32,
+}
+
+/// The statements of a block, stored contiguously in the arena.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StmtList {
+	start: u32,
+	end: u32,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct Arena {
+	exprs: Vec<Expr>,
+	stmts: Vec<Stmt>,
+	expr_lists: Vec<ExprId>,
+	stmt_lists: Vec<StmtId>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Decl {
+	pub kind: DeclKind,
+	pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum DeclKind {
+	Ty(TyDecl),
+	Fn(FnDef),
+	Fixity(FixityDecl),
+	Error,
+}
+
+#[derive(Clone, Debug)]
+pub struct Stmt {
+	pub kind: StmtKind,
+	pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum StmtKind {
+	TyDecl(TyDecl),
+	VarDef(VarDef),
+	Expr(ExprId),
+}
+
+#[derive(Clone, Debug)]
+pub struct Expr {
+	pub kind: ExprKind,
+	pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub enum ExprKind {
+	Boolean(bool),
+	Integer(i64),
+	Float(f64),
+	String(String),
+	Char(char),
+	Identifier(String),
+	If(ExprId, ExprId, Option<ExprId>),
+	While(ExprId, ExprId),
+	Call(ExprId, ExprList),
+	Unary(UnaryOp, ExprId),
+	Binary(BinaryOp, ExprId, ExprId),
+	Block(StmtList),
+	Error,
+}
+
+#[derive(Clone, Debug)]
+pub struct FnDef {
+	pub func: Identifier,
+	pub params: Vec<Identifier>,
+	pub body: ExprId,
+	pub docs: Vec<DocComment>,
+	pub span: Span,
+}
+
+#[derive(Clone, Debug)]
+pub struct VarDef {
+	pub var: Identifier,
+	pub value: ExprId,
+	pub span: Span,
+}
+
+impl ExprList {
+	pub fn len(&self) -> usize {
+		(self.end - self.start) as usize
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.start == self.end
+	}
+}
+
+impl StmtList {
+	pub fn len(&self) -> usize {
+		(self.end - self.start) as usize
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.start == self.end
+	}
+}
+
+impl Arena {
+	pub fn new() -> Self {
+		Self::default()
+	}
+
+	pub fn alloc_expr(&mut self, expr: Expr) -> ExprId {
+		self.exprs.push(expr);
+		ExprId(self.exprs.len() as u32 - 1)
+	}
+
+	pub fn alloc_stmt(&mut self, stmt: Stmt) -> StmtId {
+		self.stmts.push(stmt);
+		StmtId(self.stmts.len() as u32 - 1)
+	}
+
+	pub fn alloc_exprs(&mut self, exprs: impl IntoIterator<Item = ExprId>) -> ExprList {
+		let start = self.expr_lists.len() as u32;
+		self.expr_lists.extend(exprs);
+		let end = self.expr_lists.len() as u32;
+
+		ExprList { start, end }
+	}
+
+	pub fn alloc_stmts(&mut self, stmts: impl IntoIterator<Item = StmtId>) -> StmtList {
+		let start = self.stmt_lists.len() as u32;
+		self.stmt_lists.extend(stmts);
+		let end = self.stmt_lists.len() as u32;
+
+		StmtList { start, end }
+	}
+
+	pub fn exprs(&self) -> &[Expr] {
+		&self.exprs
+	}
+
+	pub fn stmts(&self) -> &[Stmt] {
+		&self.stmts
+	}
+}
+
+impl Index<ExprId> for Arena {
+	type Output = Expr;
+
+	fn index(&self, id: ExprId) -> &Expr {
+		&self.exprs[id.0 as usize]
+	}
+}
+
+impl IndexMut<ExprId> for Arena {
+	fn index_mut(&mut self, id: ExprId) -> &mut Expr {
+		&mut self.exprs[id.0 as usize]
+	}
+}
+
+impl Index<StmtId> for Arena {
+	type Output = Stmt;
+
+	fn index(&self, id: StmtId) -> &Stmt {
+		&self.stmts[id.0 as usize]
+	}
+}
+
+impl Index<ExprList> for Arena {
+	type Output = [ExprId];
+
+	fn index(&self, list: ExprList) -> &[ExprId] {
+		&self.expr_lists[list.start as usize..list.end as usize]
+	}
+}
+
+impl Index<StmtList> for Arena {
+	type Output = [StmtId];
+
+	fn index(&self, list: StmtList) -> &[StmtId] {
+		&self.stmt_lists[list.start as usize..list.end as usize]
+	}
+}
+
+impl TreeBuilder for Arena {
+	type Expr = ExprId;
+	type Stmt = StmtId;
+	type Decl = Decl;
+	type Output = ArenaAst;
+
+	fn leaf(&mut self, leaf: Leaf, span: Span) -> ExprId {
+		let kind = match leaf {
+			Leaf::Boolean(b) => ExprKind::Boolean(b),
+			Leaf::Integer(i) => ExprKind::Integer(i),
+			Leaf::Float(f) => ExprKind::Float(f),
+			Leaf::String(s) => ExprKind::String(s),
+			Leaf::Char(c) => ExprKind::Char(c),
+			Leaf::Identifier(i) => ExprKind::Identifier(i),
+			Leaf::Error => ExprKind::Error,
+		};
+
+		self.alloc_expr(Expr { kind, span })
+	}
+
+	fn if_expr(
+		&mut self,
+		cond: ExprId,
+		then_expr: ExprId,
+		else_expr: Option<ExprId>,
+		span: Span,
+	) -> ExprId {
+		self.alloc_expr(Expr {
+			kind: ExprKind::If(cond, then_expr, else_expr),
+			span,
+		})
+	}
+
+	fn while_expr(&mut self, cond: ExprId, body: ExprId, span: Span) -> ExprId {
+		self.alloc_expr(Expr {
+			kind: ExprKind::While(cond, body),
+			span,
+		})
+	}
+
+	fn call(&mut self, func: ExprId, args: impl IntoIterator<Item = ExprId>, span: Span) -> ExprId {
+		let args = self.alloc_exprs(args);
+
+		self.alloc_expr(Expr {
+			kind: ExprKind::Call(func, args),
+			span,
+		})
+	}
+
+	fn unary(&mut self, un_op: UnaryOp, operand: ExprId, span: Span) -> ExprId {
+		self.alloc_expr(Expr {
+			kind: ExprKind::Unary(un_op, operand),
+			span,
+		})
+	}
+
+	fn binary(&mut self, bin_op: BinaryOp, lhs: ExprId, rhs: ExprId, span: Span) -> ExprId {
+		self.alloc_expr(Expr {
+			kind: ExprKind::Binary(bin_op, lhs, rhs),
+			span,
+		})
+	}
+
+	fn block(&mut self, stmts: impl IntoIterator<Item = StmtId>, span: Span) -> ExprId {
+		let stmts = self.alloc_stmts(stmts);
+
+		self.alloc_expr(Expr {
+			kind: ExprKind::Block(stmts),
+			span,
+		})
+	}
+
+	fn span(&self, expr: &ExprId) -> Span {
+		self[*expr].span
+	}
+
+	fn set_span(&mut self, expr: &mut ExprId, span: Span) {
+		self[*expr].span = span;
+	}
+
+	fn ty_decl_stmt(&mut self, ty_decl: TyDecl) -> StmtId {
+		let span = ty_decl.span;
+
+		self.alloc_stmt(Stmt {
+			kind: StmtKind::TyDecl(ty_decl),
+			span,
+		})
+	}
+
+	fn var_def(&mut self, var: Identifier, value: ExprId, span: Span) -> StmtId {
+		let var_def = VarDef { var, value, span };
+
+		self.alloc_stmt(Stmt {
+			kind: StmtKind::VarDef(var_def),
+			span,
+		})
+	}
+
+	fn expr_stmt(&mut self, expr: ExprId, span: Span) -> StmtId {
+		self.alloc_stmt(Stmt {
+			kind: StmtKind::Expr(expr),
+			span,
+		})
+	}
+
+	fn ty_decl(&mut self, ty_decl: TyDecl) -> Decl {
+		let span = ty_decl.span;
+
+		Decl {
+			kind: DeclKind::Ty(ty_decl),
+			span,
+		}
+	}
+
+	fn fn_def(
+		&mut self,
+		func: Identifier,
+		params: Vec<Identifier>,
+		body: ExprId,
+		docs: Vec<DocComment>,
+		span: Span,
+	) -> Decl {
+		let fn_def = FnDef {
+			func,
+			params,
+			body,
+			docs,
+			span,
+		};
+
+		Decl {
+			kind: DeclKind::Fn(fn_def),
+			span,
+		}
+	}
+
+	fn fixity_decl(&mut self, fixity_decl: FixityDecl) -> Decl {
+		let span = fixity_decl.span;
+
+		Decl {
+			kind: DeclKind::Fixity(fixity_decl),
+			span,
+		}
+	}
+
+	fn error_decl(&mut self, span: Span) -> Decl {
+		Decl {
+			kind: DeclKind::Error,
+			span,
+		}
+	}
+
+	fn as_fixity_decl(decl: &Decl) -> Option<&FixityDecl> {
+		match &decl.kind {
+			DeclKind::Fixity(fixity_decl) => Some(fixity_decl),
+			_ => None,
+		}
+	}
+
+	fn finish(self, decls: Vec<Decl>) -> ArenaAst {
+		ArenaAst { decls, arena: self }
+	}
+}
+
+impl ArenaAst {
+	/// Moves every node into the boxed representation, numbering nodes in
+	/// pre-order.
+	pub fn into_ast(self) -> ast::Ast {
+		let Arena {
+			exprs,
+			stmts,
+			expr_lists,
+			stmt_lists,
+		} = self.arena;
+
+		let mut unpack = Unpack {
+			exprs: exprs.into_iter().map(Some).collect(),
+			stmts: stmts.into_iter().map(Some).collect(),
+			expr_lists,
+			stmt_lists,
+			next: 0,
+		};
+
+		self.decls
+			.into_iter()
+			.map(|decl| unpack.decl(decl))
+			.collect()
+	}
+}
+
+// every node is referenced exactly once, so each is taken out of its slot
+struct Unpack {
+	exprs: Vec<Option<Expr>>,
+	stmts: Vec<Option<Stmt>>,
+	expr_lists: Vec<ExprId>,
+	stmt_lists: Vec<StmtId>,
+	next: usize,
+}
+
+impl Unpack {
+	fn next_id(&mut self) -> NodeId {
+		let id = NodeId(self.next);
+		self.next += 1;
+
+		id
+	}
+
+	fn decl(&mut self, decl: Decl) -> ast::Decl {
+		let id = self.next_id();
+
+		let kind = match decl.kind {
+			DeclKind::Ty(ty_decl) => ast::DeclKind::Ty(self.ty_decl(ty_decl)),
+			DeclKind::Fn(fn_def) => ast::DeclKind::Fn(self.fn_def(fn_def)),
+			DeclKind::Fixity(fixity_decl) => ast::DeclKind::Fixity(self.fixity_decl(fixity_decl)),
+			DeclKind::Error => ast::DeclKind::Error,
+		};
+
+		ast::Decl {
+			id,
+			kind,
+			span: decl.span,
+		}
+	}
+
+	fn ty_decl(&mut self, ty_decl: TyDecl) -> TyDecl {
+		TyDecl {
+			identifier: self.identifier(ty_decl.identifier),
+			..ty_decl
+		}
+	}
+
+	fn fn_def(&mut self, fn_def: FnDef) -> ast::FnDef {
+		let FnDef {
+			func,
+			params,
+			body,
+			docs,
+			span,
+		} = fn_def;
+
+		ast::FnDef {
+			func: self.identifier(func),
+			params: params
+				.into_iter()
+				.map(|param| self.identifier(param))
+				.collect(),
+			body: self.expr(body),
+			docs,
+			span,
+		}
+	}
+
+	fn fixity_decl(&mut self, fixity_decl: FixityDecl) -> FixityDecl {
+		let ops = fixity_decl
+			.ops
+			.into_iter()
+			.map(|op| self.identifier(op))
+			.collect();
+
+		FixityDecl { ops, ..fixity_decl }
+	}
+
+	fn stmt(&mut self, id: StmtId) -> ast::Stmt {
+		let Stmt { kind, span } = self.stmts[id.0 as usize].take().unwrap();
+		let id = self.next_id();
+
+		let kind = match kind {
+			StmtKind::TyDecl(ty_decl) => ast::StmtKind::TyDecl(self.ty_decl(ty_decl)),
+			StmtKind::VarDef(VarDef { var, value, span }) => ast::StmtKind::VarDef(ast::VarDef {
+				var: self.identifier(var),
+				value: self.expr(value),
+				span,
+			}),
+			StmtKind::Expr(expr) => ast::StmtKind::Expr(self.expr(expr)),
+		};
+
+		ast::Stmt { id, kind, span }
+	}
+
+	fn expr(&mut self, id: ExprId) -> ast::Expr {
+		let Expr { kind, span } = self.exprs[id.0 as usize].take().unwrap();
+		let id = self.next_id();
+
+		let kind = match kind {
+			ExprKind::Boolean(b) => ast::ExprKind::Boolean(b),
+			ExprKind::Integer(i) => ast::ExprKind::Integer(i),
+			ExprKind::Float(f) => ast::ExprKind::Float(f),
+			ExprKind::String(s) => ast::ExprKind::String(s),
+			ExprKind::Char(c) => ast::ExprKind::Char(c),
+			ExprKind::Identifier(i) => ast::ExprKind::Identifier(i),
+			ExprKind::If(cond, t, e) => ast::ExprKind::If(
+				Box::new(self.expr(cond)),
+				Box::new(self.expr(t)),
+				e.map(|e| Box::new(self.expr(e))),
+			),
+			ExprKind::While(cond, body) => {
+				ast::ExprKind::While(Box::new(self.expr(cond)), Box::new(self.expr(body)))
+			},
+			ExprKind::Call(func, args) => {
+				let func = self.expr(func);
+				let args = (args.start..args.end)
+					.map(|i| self.expr(self.expr_lists[i as usize]))
+					.collect();
+
+				ast::ExprKind::Call(Box::new(func), args)
+			},
+			ExprKind::Unary(un_op, operand) => {
+				ast::ExprKind::Unary(un_op, Box::new(self.expr(operand)))
+			},
+			ExprKind::Binary(bin_op, lhs, rhs) => {
+				ast::ExprKind::Binary(bin_op, Box::new(self.expr(lhs)), Box::new(self.expr(rhs)))
+			},
+			ExprKind::Block(stmts) => {
+				let stmts = (stmts.start..stmts.end)
+					.map(|i| self.stmt(self.stmt_lists[i as usize]))
+					.collect();
+
+				ast::ExprKind::Block(stmts)
+			},
+			ExprKind::Error => ast::ExprKind::Error,
+		};
+
+		ast::Expr { id, kind, span }
+	}
+
+	fn identifier(&mut self, identifier: Identifier) -> Identifier {
+		Identifier {
+			id: self.next_id(),
+			..identifier
+		}
+	}
+}
@@ -1,91 +1,344 @@
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::mem;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
 use crate::common::span::Span;
+use crate::parser::visit::{self, VisitorMut};
 
 pub type Ast = Vec<Decl>;
 
-#[derive(Debug)]
+/// Identifies a declaration, statement, expression or identifier within a tree.
+/// The parser numbers nodes in pre-order from zero, so ids are dense and stable
+/// for a given source; nodes built by later passes hold [`NodeId::DUMMY`] until
+/// [`assign_ids`](crate::parser::ids::assign_ids) runs again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct NodeId(pub usize);
+
+impl NodeId {
+	pub const DUMMY: NodeId = NodeId(usize::MAX);
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Decl {
+	pub id: NodeId,
 	pub kind: DeclKind,
 	pub span: Span,
 }
 
-#[derive(Debug)]
+impl Decl {
+	/// The doc comments of the declaration, which are stored on the
+	/// [`TyDecl`], [`FnDef`] or [`FixityDecl`] it holds.
+	pub fn docs(&self) -> &[DocComment] {
+		match &self.kind {
+			DeclKind::Ty(ty_decl) => &ty_decl.docs,
+			DeclKind::Fn(fn_def) => &fn_def.docs,
+			DeclKind::Fixity(fixity_decl) => &fixity_decl.docs,
+			DeclKind::Error => &[],
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum DeclKind {
 	Ty(TyDecl),
 	Fn(FnDef),
+	Fixity(FixityDecl),
+	Error,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Stmt {
+	pub id: NodeId,
 	pub kind: StmtKind,
 	pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum StmtKind {
 	TyDecl(TyDecl),
 	VarDef(VarDef),
 	Expr(Expr),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Expr {
+	pub id: NodeId,
 	pub kind: ExprKind,
 	pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum ExprKind {
 	Boolean(bool),
 	Integer(i64),
+	Float(f64),
+	String(String),
+	Char(char),
 	Identifier(String),
 	If(Box<Expr>, Box<Expr>, Option<Box<Expr>>),
 	While(Box<Expr>, Box<Expr>),
 	Call(Box<Expr>, Vec<Expr>),
+	Unary(UnaryOp, Box<Expr>),
+	Binary(BinaryOp, Box<Expr>, Box<Expr>),
 	Block(Vec<Stmt>),
+	Error,
 }
 
-#[derive(Debug)]
+// floats are compared and hashed by their bits, so `NaN` equals itself and the
+// AST can implement `Eq`
+impl PartialEq for ExprKind {
+	fn eq(&self, other: &Self) -> bool {
+		match (self, other) {
+			(ExprKind::Boolean(a), ExprKind::Boolean(b)) => a == b,
+			(ExprKind::Integer(a), ExprKind::Integer(b)) => a == b,
+			(ExprKind::Float(a), ExprKind::Float(b)) => a.to_bits() == b.to_bits(),
+			(ExprKind::String(a), ExprKind::String(b)) => a == b,
+			(ExprKind::Char(a), ExprKind::Char(b)) => a == b,
+			(ExprKind::Identifier(a), ExprKind::Identifier(b)) => a == b,
+			(ExprKind::If(a1, a2, a3), ExprKind::If(b1, b2, b3)) => {
+				a1 == b1 && a2 == b2 && a3 == b3
+			},
+			(ExprKind::While(a1, a2), ExprKind::While(b1, b2)) => a1 == b1 && a2 == b2,
+			(ExprKind::Call(a1, a2), ExprKind::Call(b1, b2)) => a1 == b1 && a2 == b2,
+			(ExprKind::Unary(a1, a2), ExprKind::Unary(b1, b2)) => a1 == b1 && a2 == b2,
+			(ExprKind::Binary(a1, a2, a3), ExprKind::Binary(b1, b2, b3)) => {
+				a1 == b1 && a2 == b2 && a3 == b3
+			},
+			(ExprKind::Block(a), ExprKind::Block(b)) => a == b,
+			(ExprKind::Error, ExprKind::Error) => true,
+			_ => false,
+		}
+	}
+}
+
+impl Eq for ExprKind {}
+
+impl Hash for ExprKind {
+	fn hash<H: Hasher>(&self, state: &mut H) {
+		mem::discriminant(self).hash(state);
+
+		match self {
+			ExprKind::Boolean(b) => b.hash(state),
+			ExprKind::Integer(i) => i.hash(state),
+			ExprKind::Float(f) => f.to_bits().hash(state),
+			ExprKind::String(s) => s.hash(state),
+			ExprKind::Char(c) => c.hash(state),
+			ExprKind::Identifier(i) => i.hash(state),
+			ExprKind::If(cond, t, e) => (cond, t, e).hash(state),
+			ExprKind::While(cond, body) => (cond, body).hash(state),
+			ExprKind::Call(func, args) => (func, args).hash(state),
+			ExprKind::Unary(un_op, operand) => (un_op, operand).hash(state),
+			ExprKind::Binary(bin_op, lhs, rhs) => (bin_op, lhs, rhs).hash(state),
+			ExprKind::Block(stmts) => stmts.hash(state),
+			ExprKind::Error => (),
+		}
+	}
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Ty {
 	pub kind: TyKind,
 	pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum TyKind {
 	Single(String),
 	Tuple(Vec<Ty>),
 	Function(Vec<Ty>, Box<Ty>),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TyDecl {
 	pub identifier: Identifier,
 	pub ty: Ty,
+	pub docs: Vec<DocComment>,
 	pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct FnDef {
 	pub func: Identifier,
 	pub params: Vec<Identifier>,
 	pub body: Expr,
+	pub docs: Vec<DocComment>,
 	pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct FixityDecl {
+	pub assoc: OpAssoc,
+	pub prec: usize,
+	pub ops: Vec<Identifier>,
+	pub docs: Vec<DocComment>,
+	pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct VarDef {
 	pub var: Identifier,
 	pub value: Expr,
 	pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Identifier {
+	pub id: NodeId,
 	pub name: String,
 	pub span: Span,
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DocComment {
+	pub text: String,
+	pub span: Span,
+}
+
+pub fn fixities(ast: &Ast) -> HashMap<String, (OpAssoc, usize)> {
+	collect_fixities(ast.iter().filter_map(|decl| match &decl.kind {
+		DeclKind::Fixity(fixity_decl) => Some(fixity_decl),
+		_ => None,
+	}))
+}
+
+pub(crate) fn collect_fixities<'a>(
+	fixity_decls: impl IntoIterator<Item = &'a FixityDecl>,
+) -> HashMap<String, (OpAssoc, usize)> {
+	let mut fixities = HashMap::new();
+
+	for fixity_decl in fixity_decls {
+		for op in &fixity_decl.ops {
+			let fixity = (fixity_decl.assoc, fixity_decl.prec);
+			fixities.insert(op.name.clone(), fixity);
+		}
+	}
+
+	fixities
+}
+
+/// Compares two trees structurally, ignoring every span and [`NodeId`], so
+/// sources that only differ in layout compare equal, as do trees numbered by
+/// different passes.
+pub fn ast_eq_ignoring_spans(a: &Ast, b: &Ast) -> bool {
+	let mut a = a.clone();
+	let mut b = b.clone();
+
+	EraseLocations.visit_ast(&mut a);
+	EraseLocations.visit_ast(&mut b);
+
+	a == b
+}
+
+// resets spans and ids, which both only locate a node
+struct EraseLocations;
+
+impl EraseLocations {
+	fn erase_docs(&self, docs: &mut [DocComment]) {
+		for doc in docs {
+			doc.span = Span::pair(0);
+		}
+	}
+}
+
+impl VisitorMut for EraseLocations {
+	fn visit_decl(&mut self, decl: &mut Decl) {
+		decl.id = NodeId::DUMMY;
+		decl.span = Span::pair(0);
+		visit::walk_decl_mut(self, decl);
+	}
+
+	fn visit_ty_decl(&mut self, ty_decl: &mut TyDecl) {
+		ty_decl.span = Span::pair(0);
+		self.erase_docs(&mut ty_decl.docs);
+		visit::walk_ty_decl_mut(self, ty_decl);
+	}
+
+	fn visit_fn_def(&mut self, fn_def: &mut FnDef) {
+		fn_def.span = Span::pair(0);
+		self.erase_docs(&mut fn_def.docs);
+		visit::walk_fn_def_mut(self, fn_def);
+	}
+
+	fn visit_fixity_decl(&mut self, fixity_decl: &mut FixityDecl) {
+		fixity_decl.span = Span::pair(0);
+		self.erase_docs(&mut fixity_decl.docs);
+		visit::walk_fixity_decl_mut(self, fixity_decl);
+	}
+
+	fn visit_stmt(&mut self, stmt: &mut Stmt) {
+		stmt.id = NodeId::DUMMY;
+		stmt.span = Span::pair(0);
+		visit::walk_stmt_mut(self, stmt);
+	}
+
+	fn visit_var_def(&mut self, var_def: &mut VarDef) {
+		var_def.span = Span::pair(0);
+		visit::walk_var_def_mut(self, var_def);
+	}
+
+	fn visit_expr(&mut self, expr: &mut Expr) {
+		expr.id = NodeId::DUMMY;
+		expr.span = Span::pair(0);
+
+		match &mut expr.kind {
+			ExprKind::Unary(un_op, _) => un_op.span = Span::pair(0),
+			ExprKind::Binary(bin_op, _, _) => bin_op.span = Span::pair(0),
+			_ => (),
+		}
+
+		visit::walk_expr_mut(self, expr);
+	}
+
+	fn visit_ty(&mut self, ty: &mut Ty) {
+		ty.span = Span::pair(0);
+		visit::walk_ty_mut(self, ty);
+	}
+
+	fn visit_identifier(&mut self, identifier: &mut Identifier) {
+		identifier.id = NodeId::DUMMY;
+		identifier.span = Span::pair(0);
+	}
+}
+
+pub const MIN_PREC: usize = 1;
+pub const MAX_PREC: usize = 9;
+
+// operands of prefix operators are parsed like the right hand side of a
+// right-associative operator at this level, so `-a * b` is `(-a) * b` but
+// `-a ** b` is `-(a ** b)`
+pub const UNARY_PREC: usize = 7;
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UnaryOp {
+	pub lexeme: String,
+	pub span: Span,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct BinaryOp {
 	pub lexeme: String,
 	pub span: Span,
@@ -113,7 +366,8 @@ impl BinaryOp {
 	}
 }
 
-#[derive(Debug)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum OpAssoc {
 	Left,
 	Right,
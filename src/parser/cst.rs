@@ -0,0 +1,518 @@
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter, Result};
+use std::rc::Rc;
+
+use crate::common::span::Span;
+use crate::lexer::token::{Token, TokenKind};
+use crate::parser::ast::*;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeKind {
+	Root,
+	FnDef,
+	TyDecl,
+	VarDef,
+	FixityDecl,
+	ParamList,
+	ArgList,
+	Literal,
+	Name,
+	Paren,
+	If,
+	While,
+	Call,
+	Unary,
+	Binary,
+	Block,
+	SingleTy,
+	TupleTy,
+	FunctionTy,
+	Error,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TriviaKind {
+	Whitespace,
+	Comment,
+	DocComment,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum LeafKind {
+	Token(TokenKind),
+	Trivia(TriviaKind),
+}
+
+/// An immutable, position-independent node. Green nodes only know their
+/// width, so identical subtrees can be shared between trees.
+#[derive(Debug)]
+pub struct GreenNode {
+	pub kind: NodeKind,
+	pub width: usize,
+	pub children: Vec<GreenElement>,
+}
+
+#[derive(Debug)]
+pub struct GreenToken {
+	pub kind: LeafKind,
+	pub text: String,
+}
+
+#[derive(Clone, Debug)]
+pub enum GreenElement {
+	Node(Rc<GreenNode>),
+	Token(Rc<GreenToken>),
+}
+
+/// A green node positioned in the source, computing absolute spans of its
+/// children on demand.
+#[derive(Clone, Debug)]
+pub struct SyntaxNode {
+	green: Rc<GreenNode>,
+	offset: usize,
+}
+
+#[derive(Clone, Debug)]
+pub struct SyntaxToken {
+	green: Rc<GreenToken>,
+	offset: usize,
+}
+
+#[derive(Clone, Debug)]
+pub enum SyntaxElement {
+	Node(SyntaxNode),
+	Token(SyntaxToken),
+}
+
+/// Maps the id of every AST node to the part of the CST it was parsed from.
+/// Identifiers map to their token and other nodes to a syntax node, which an
+/// expression statement shares with its expression, as does a parenthesized
+/// expression with its parentheses.
+pub type SyntaxMap = HashMap<NodeId, SyntaxElement>;
+
+impl GreenNode {
+	pub fn new(kind: NodeKind, children: Vec<GreenElement>) -> Self {
+		let width = children.iter().map(GreenElement::width).sum();
+
+		Self {
+			kind,
+			width,
+			children,
+		}
+	}
+}
+
+impl GreenElement {
+	pub fn width(&self) -> usize {
+		match self {
+			GreenElement::Node(node) => node.width,
+			GreenElement::Token(token) => token.text.len(),
+		}
+	}
+}
+
+impl SyntaxNode {
+	pub fn new_root(green: Rc<GreenNode>) -> Self {
+		Self { green, offset: 0 }
+	}
+
+	pub fn kind(&self) -> NodeKind {
+		self.green.kind
+	}
+
+	pub fn span(&self) -> Span {
+		Span::new(self.offset, self.offset + self.green.width)
+	}
+
+	pub fn green(&self) -> &Rc<GreenNode> {
+		&self.green
+	}
+
+	pub fn children(&self) -> impl Iterator<Item = SyntaxElement> + '_ {
+		let mut offset = self.offset;
+
+		self.green.children.iter().map(move |child| {
+			let element = match child {
+				GreenElement::Node(green) => SyntaxElement::Node(SyntaxNode {
+					green: green.clone(),
+					offset,
+				}),
+				GreenElement::Token(green) => SyntaxElement::Token(SyntaxToken {
+					green: green.clone(),
+					offset,
+				}),
+			};
+
+			offset += child.width();
+
+			element
+		})
+	}
+
+	pub fn tokens(&self) -> Vec<SyntaxToken> {
+		let mut tokens = Vec::new();
+
+		for child in self.children() {
+			match child {
+				SyntaxElement::Node(node) => tokens.append(&mut node.tokens()),
+				SyntaxElement::Token(token) => tokens.push(token),
+			}
+		}
+
+		tokens
+	}
+}
+
+impl SyntaxElement {
+	pub fn span(&self) -> Span {
+		match self {
+			SyntaxElement::Node(node) => node.span(),
+			SyntaxElement::Token(token) => token.span(),
+		}
+	}
+}
+
+impl SyntaxToken {
+	pub fn kind(&self) -> &LeafKind {
+		&self.green.kind
+	}
+
+	pub fn text(&self) -> &str {
+		&self.green.text
+	}
+
+	pub fn span(&self) -> Span {
+		Span::new(self.offset, self.offset + self.green.text.len())
+	}
+
+	pub fn is_trivia(&self) -> bool {
+		matches!(self.green.kind, LeafKind::Trivia(_))
+	}
+}
+
+impl Display for GreenNode {
+	fn fmt(&self, f: &mut Formatter) -> Result {
+		for child in &self.children {
+			match child {
+				GreenElement::Node(node) => write!(f, "{node}")?,
+				GreenElement::Token(token) => write!(f, "{}", token.text)?,
+			}
+		}
+
+		Ok(())
+	}
+}
+
+impl Display for SyntaxNode {
+	fn fmt(&self, f: &mut Formatter) -> Result {
+		write!(f, "{}", self.green)
+	}
+}
+
+/// Builds a green tree from the tokens the parser consumes. Text between two
+/// tokens is split into trivia, so the finished tree covers the whole source.
+///
+/// The tree is built alongside the AST rather than the AST being lowered from
+/// it, so plain [`parse`](crate::parse) skips the builder entirely and callers
+/// that never look at trivia do not pay for it. [`syntax_map`] links the two
+/// trees afterwards.
+pub(crate) struct CstBuilder<'a> {
+	source: &'a str,
+	offset: usize,
+	parents: Vec<(NodeKind, usize)>,
+	children: Vec<GreenElement>,
+}
+
+impl<'a> CstBuilder<'a> {
+	pub fn new(source: &'a str) -> Self {
+		Self {
+			source,
+			offset: 0,
+			parents: vec![(NodeKind::Root, 0)],
+			children: Vec::new(),
+		}
+	}
+
+	pub fn start_node(&mut self, kind: NodeKind, start: usize) {
+		self.trivia(start);
+		self.parents.push((kind, self.children.len()));
+	}
+
+	// a checkpoint lets a node be started after its first child was built,
+	// e.g. the lhs of a binary expression
+	pub fn checkpoint(&mut self, start: usize) -> usize {
+		self.trivia(start);
+		self.children.len()
+	}
+
+	pub fn start_node_at(&mut self, checkpoint: usize, kind: NodeKind) {
+		self.parents.push((kind, checkpoint));
+	}
+
+	pub fn finish_node(&mut self) {
+		let (kind, first) = self.parents.pop().unwrap();
+		let children = self.children.split_off(first);
+
+		let node = GreenNode::new(kind, children);
+		self.children.push(GreenElement::Node(Rc::new(node)));
+	}
+
+	pub fn depth(&self) -> usize {
+		self.parents.len()
+	}
+
+	pub fn token(&mut self, token: &Token) {
+		// synthetic layout tokens have no text of their own
+		if token.span.0 == token.span.1 {
+			return;
+		}
+
+		self.trivia(token.span.0);
+
+		// error tokens from layout checks may cover text that was already
+		// emitted, so only the part past the current offset is kept
+		let start = token.span.0.max(self.offset);
+
+		if start >= token.span.1 {
+			return;
+		}
+
+		let token = GreenToken {
+			kind: LeafKind::Token(token.kind.clone()),
+			text: String::from(&self.source[start..token.span.1]),
+		};
+
+		self.offset += token.text.len();
+		self.children.push(GreenElement::Token(Rc::new(token)));
+	}
+
+	pub fn finish(mut self) -> SyntaxNode {
+		self.trivia(self.source.len());
+
+		while !self.parents.is_empty() {
+			self.finish_node();
+		}
+
+		match self.children.pop() {
+			Some(GreenElement::Node(green)) => SyntaxNode::new_root(green),
+			_ => unreachable!(),
+		}
+	}
+
+	fn trivia(&mut self, end: usize) {
+		if end <= self.offset {
+			return;
+		}
+
+		let mut rest = &self.source[self.offset..end];
+
+		while !rest.is_empty() {
+			let start = end - rest.len();
+
+			let (kind, len) = if rest.starts_with("##") && is_line_start(self.source, start) {
+				(TriviaKind::DocComment, line_len(rest))
+			} else if rest.starts_with("#[") {
+				(TriviaKind::Comment, block_comment_len(rest))
+			} else if rest.starts_with('#') {
+				(TriviaKind::Comment, line_len(rest))
+			} else {
+				(TriviaKind::Whitespace, whitespace_len(rest))
+			};
+
+			let token = GreenToken {
+				kind: LeafKind::Trivia(kind),
+				text: String::from(&rest[..len]),
+			};
+
+			self.children.push(GreenElement::Token(Rc::new(token)));
+			rest = &rest[len..];
+		}
+
+		self.offset = end;
+	}
+}
+
+/// Pairs every node of `ast` with the part of `root` the parser built for it,
+/// following the shape the parser gives each kind of node.
+pub(crate) fn syntax_map(ast: &Ast, root: &SyntaxNode) -> SyntaxMap {
+	let mut link = Link {
+		map: HashMap::new(),
+	};
+
+	for (decl, node) in ast.iter().zip(child_nodes(root)) {
+		link.decl(decl, node);
+	}
+
+	link.map
+}
+
+struct Link {
+	map: SyntaxMap,
+}
+
+impl Link {
+	fn node(&mut self, id: NodeId, node: &SyntaxNode) {
+		self.map.insert(id, SyntaxElement::Node(node.clone()));
+	}
+
+	fn identifiers<'a>(
+		&mut self,
+		identifiers: impl IntoIterator<Item = &'a Identifier>,
+		node: &SyntaxNode,
+	) {
+		let tokens = child_tokens(node).filter(|token| {
+			matches!(
+				token.kind(),
+				LeafKind::Token(TokenKind::Identifier(_) | TokenKind::Operator(_))
+			)
+		});
+
+		for (identifier, token) in identifiers.into_iter().zip(tokens) {
+			self.map.insert(identifier.id, SyntaxElement::Token(token));
+		}
+	}
+
+	fn decl(&mut self, decl: &Decl, node: SyntaxNode) {
+		self.node(decl.id, &node);
+
+		match &decl.kind {
+			DeclKind::Ty(ty_decl) => self.identifiers([&ty_decl.identifier], &node),
+			DeclKind::Fn(fn_def) => self.fn_def(fn_def, &node),
+			DeclKind::Fixity(fixity_decl) => self.identifiers(&fixity_decl.ops, &node),
+			DeclKind::Error => (),
+		}
+	}
+
+	fn fn_def(&mut self, fn_def: &FnDef, node: &SyntaxNode) {
+		self.identifiers([&fn_def.func], node);
+
+		let mut nodes = child_nodes(node);
+
+		if let Some(params) = nodes.next() {
+			self.identifiers(&fn_def.params, &params);
+		}
+
+		if let Some(body) = nodes.next() {
+			self.expr(&fn_def.body, body);
+		}
+	}
+
+	fn stmt(&mut self, stmt: &Stmt, node: SyntaxNode) {
+		self.node(stmt.id, &node);
+
+		match &stmt.kind {
+			StmtKind::TyDecl(ty_decl) => self.identifiers([&ty_decl.identifier], &node),
+			StmtKind::VarDef(var_def) => {
+				self.identifiers([&var_def.var], &node);
+				self.exprs([&var_def.value], &node);
+			},
+			StmtKind::Expr(expr) => self.expr(expr, node),
+		}
+	}
+
+	fn expr(&mut self, expr: &Expr, node: SyntaxNode) {
+		self.node(expr.id, &node);
+
+		let mut node = node;
+
+		while node.kind() == NodeKind::Paren {
+			let inner = child_nodes(&node).next();
+
+			match inner {
+				Some(inner) => node = inner,
+				None => return,
+			}
+		}
+
+		match &expr.kind {
+			ExprKind::If(cond, then_expr, else_expr) => {
+				let exprs = [Some(cond), Some(then_expr), else_expr.as_ref()];
+				self.exprs(exprs.into_iter().flatten().map(|expr| &**expr), &node);
+			},
+			ExprKind::While(cond, body) => self.exprs([&**cond, body], &node),
+			ExprKind::Call(func, args) => {
+				let mut nodes = child_nodes(&node);
+
+				if let Some(func_node) = nodes.next() {
+					self.expr(func, func_node);
+				}
+
+				if let Some(arg_list) = nodes.next() {
+					self.exprs(args, &arg_list);
+				}
+			},
+			ExprKind::Unary(_, operand) => self.exprs([&**operand], &node),
+			ExprKind::Binary(_, lhs, rhs) => self.exprs([&**lhs, rhs], &node),
+			ExprKind::Block(stmts) => {
+				for (stmt, stmt_node) in stmts.iter().zip(child_nodes(&node)) {
+					self.stmt(stmt, stmt_node);
+				}
+			},
+			_ => (),
+		}
+	}
+
+	fn exprs<'a>(&mut self, exprs: impl IntoIterator<Item = &'a Expr>, node: &SyntaxNode) {
+		for (expr, expr_node) in exprs.into_iter().zip(child_nodes(node)) {
+			self.expr(expr, expr_node);
+		}
+	}
+}
+
+fn child_nodes(node: &SyntaxNode) -> impl Iterator<Item = SyntaxNode> + '_ {
+	node.children().filter_map(|child| match child {
+		SyntaxElement::Node(node) => Some(node),
+		SyntaxElement::Token(_) => None,
+	})
+}
+
+fn child_tokens(node: &SyntaxNode) -> impl Iterator<Item = SyntaxToken> + '_ {
+	node.children().filter_map(|child| match child {
+		SyntaxElement::Token(token) if !token.is_trivia() => Some(token),
+		_ => None,
+	})
+}
+
+// like the lexer, only a `##` that begins its line is a doc comment
+fn is_line_start(source: &str, index: usize) -> bool {
+	let line_start = source[..index].rfind('\n').map_or(0, |i| i + 1);
+	source[line_start..index].trim().is_empty()
+}
+
+fn line_len(text: &str) -> usize {
+	text.find('\n').unwrap_or(text.len())
+}
+
+fn whitespace_len(text: &str) -> usize {
+	match text.find(|c: char| !c.is_ascii_whitespace()) {
+		Some(0) => text.chars().next().unwrap().len_utf8(),
+		Some(len) => len,
+		None => text.len(),
+	}
+}
+
+fn block_comment_len(text: &str) -> usize {
+	let bytes = text.as_bytes();
+	let mut depth = 0;
+	let mut i = 0;
+
+	while i < bytes.len() {
+		match &bytes[i..] {
+			[b'#', b'[', ..] => {
+				depth += 1;
+				i += 2;
+			},
+			[b']', b'#', ..] => {
+				depth -= 1;
+				i += 2;
+
+				if depth == 0 {
+					return i;
+				}
+			},
+			_ => i += 1,
+		}
+	}
+
+	bytes.len()
+}
@@ -0,0 +1,181 @@
+use crate::parser::ast::*;
+
+/// Rebuilds an AST by value. Every method defaults to the matching free
+/// function, which folds the children of a node and keeps its span and id, so a
+/// pass only overrides the nodes it rewrites and calls the free function to
+/// recurse.
+pub trait Fold {
+	fn fold_ast(&mut self, ast: Ast) -> Ast {
+		fold_ast(self, ast)
+	}
+
+	fn fold_decl(&mut self, decl: Decl) -> Decl {
+		fold_decl(self, decl)
+	}
+
+	fn fold_ty_decl(&mut self, ty_decl: TyDecl) -> TyDecl {
+		fold_ty_decl(self, ty_decl)
+	}
+
+	fn fold_fn_def(&mut self, fn_def: FnDef) -> FnDef {
+		fold_fn_def(self, fn_def)
+	}
+
+	fn fold_fixity_decl(&mut self, fixity_decl: FixityDecl) -> FixityDecl {
+		fold_fixity_decl(self, fixity_decl)
+	}
+
+	fn fold_stmt(&mut self, stmt: Stmt) -> Stmt {
+		fold_stmt(self, stmt)
+	}
+
+	fn fold_var_def(&mut self, var_def: VarDef) -> VarDef {
+		fold_var_def(self, var_def)
+	}
+
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		fold_expr(self, expr)
+	}
+
+	fn fold_ty(&mut self, ty: Ty) -> Ty {
+		fold_ty(self, ty)
+	}
+
+	fn fold_identifier(&mut self, identifier: Identifier) -> Identifier {
+		identifier
+	}
+}
+
+pub fn fold_ast<F: Fold + ?Sized>(folder: &mut F, ast: Ast) -> Ast {
+	ast.into_iter().map(|decl| folder.fold_decl(decl)).collect()
+}
+
+pub fn fold_decl<F: Fold + ?Sized>(folder: &mut F, decl: Decl) -> Decl {
+	let kind = match decl.kind {
+		DeclKind::Ty(ty_decl) => DeclKind::Ty(folder.fold_ty_decl(ty_decl)),
+		DeclKind::Fn(fn_def) => DeclKind::Fn(folder.fold_fn_def(fn_def)),
+		DeclKind::Fixity(fixity_decl) => DeclKind::Fixity(folder.fold_fixity_decl(fixity_decl)),
+		DeclKind::Error => DeclKind::Error,
+	};
+
+	Decl { kind, ..decl }
+}
+
+pub fn fold_ty_decl<F: Fold + ?Sized>(folder: &mut F, ty_decl: TyDecl) -> TyDecl {
+	let TyDecl {
+		identifier,
+		ty,
+		docs,
+		span,
+	} = ty_decl;
+
+	TyDecl {
+		identifier: folder.fold_identifier(identifier),
+		ty: folder.fold_ty(ty),
+		docs,
+		span,
+	}
+}
+
+pub fn fold_fn_def<F: Fold + ?Sized>(folder: &mut F, fn_def: FnDef) -> FnDef {
+	let FnDef {
+		func,
+		params,
+		body,
+		docs,
+		span,
+	} = fn_def;
+
+	FnDef {
+		func: folder.fold_identifier(func),
+		params: params
+			.into_iter()
+			.map(|param| folder.fold_identifier(param))
+			.collect(),
+		body: folder.fold_expr(body),
+		docs,
+		span,
+	}
+}
+
+pub fn fold_fixity_decl<F: Fold + ?Sized>(folder: &mut F, fixity_decl: FixityDecl) -> FixityDecl {
+	let ops = fixity_decl
+		.ops
+		.into_iter()
+		.map(|op| folder.fold_identifier(op))
+		.collect();
+
+	FixityDecl { ops, ..fixity_decl }
+}
+
+pub fn fold_stmt<F: Fold + ?Sized>(folder: &mut F, stmt: Stmt) -> Stmt {
+	let kind = match stmt.kind {
+		StmtKind::TyDecl(ty_decl) => StmtKind::TyDecl(folder.fold_ty_decl(ty_decl)),
+		StmtKind::VarDef(var_def) => StmtKind::VarDef(folder.fold_var_def(var_def)),
+		StmtKind::Expr(expr) => StmtKind::Expr(folder.fold_expr(expr)),
+	};
+
+	Stmt { kind, ..stmt }
+}
+
+pub fn fold_var_def<F: Fold + ?Sized>(folder: &mut F, var_def: VarDef) -> VarDef {
+	let VarDef { var, value, span } = var_def;
+
+	VarDef {
+		var: folder.fold_identifier(var),
+		value: folder.fold_expr(value),
+		span,
+	}
+}
+
+pub fn fold_expr<F: Fold + ?Sized>(folder: &mut F, expr: Expr) -> Expr {
+	let Expr { id, kind, span } = expr;
+
+	let kind = match kind {
+		ExprKind::If(cond, t, e) => ExprKind::If(
+			Box::new(folder.fold_expr(*cond)),
+			Box::new(folder.fold_expr(*t)),
+			e.map(|e| Box::new(folder.fold_expr(*e))),
+		),
+		ExprKind::While(cond, body) => ExprKind::While(
+			Box::new(folder.fold_expr(*cond)),
+			Box::new(folder.fold_expr(*body)),
+		),
+		ExprKind::Call(func, args) => ExprKind::Call(
+			Box::new(folder.fold_expr(*func)),
+			args.into_iter().map(|arg| folder.fold_expr(arg)).collect(),
+		),
+		ExprKind::Unary(un_op, operand) => {
+			ExprKind::Unary(un_op, Box::new(folder.fold_expr(*operand)))
+		},
+		ExprKind::Binary(bin_op, lhs, rhs) => ExprKind::Binary(
+			bin_op,
+			Box::new(folder.fold_expr(*lhs)),
+			Box::new(folder.fold_expr(*rhs)),
+		),
+		ExprKind::Block(stmts) => ExprKind::Block(
+			stmts
+				.into_iter()
+				.map(|stmt| folder.fold_stmt(stmt))
+				.collect(),
+		),
+		kind => kind,
+	};
+
+	Expr { id, kind, span }
+}
+
+pub fn fold_ty<F: Fold + ?Sized>(folder: &mut F, ty: Ty) -> Ty {
+	let Ty { kind, span } = ty;
+
+	let kind = match kind {
+		TyKind::Single(t) => TyKind::Single(t),
+		TyKind::Tuple(tys) => TyKind::Tuple(tys.into_iter().map(|ty| folder.fold_ty(ty)).collect()),
+		TyKind::Function(params, ret) => TyKind::Function(
+			params.into_iter().map(|ty| folder.fold_ty(ty)).collect(),
+			Box::new(folder.fold_ty(*ret)),
+		),
+	};
+
+	Ty { kind, span }
+}
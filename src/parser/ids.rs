@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+
+use crate::common::span::Span;
+use crate::parser::ast::*;
+use crate::parser::visit::{self, Visitor, VisitorMut};
+
+/// A reference to any node that carries a [`NodeId`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Node<'a> {
+	Decl(&'a Decl),
+	Stmt(&'a Stmt),
+	Expr(&'a Expr),
+	Identifier(&'a Identifier),
+}
+
+impl Node<'_> {
+	pub fn id(&self) -> NodeId {
+		match self {
+			Node::Decl(decl) => decl.id,
+			Node::Stmt(stmt) => stmt.id,
+			Node::Expr(expr) => expr.id,
+			Node::Identifier(identifier) => identifier.id,
+		}
+	}
+
+	pub fn span(&self) -> Span {
+		match self {
+			Node::Decl(decl) => decl.span,
+			Node::Stmt(stmt) => stmt.span,
+			Node::Expr(expr) => expr.span,
+			Node::Identifier(identifier) => identifier.span,
+		}
+	}
+}
+
+/// Records where every node of a tree sits, so nodes can be looked up by id.
+/// Unlike [`node_map`], it holds no references into the tree, so it can be
+/// kept alongside the [`Ast`] it was built from.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct NodeMap {
+	// the parent of each node, if any, and its position among its siblings
+	locations: HashMap<NodeId, (Option<NodeId>, usize)>,
+}
+
+impl NodeMap {
+	pub fn new(ast: &Ast) -> Self {
+		let mut locate = Locate {
+			locations: HashMap::new(),
+			parents: Vec::new(),
+			next_decl: 0,
+		};
+		locate.visit_ast(ast);
+
+		Self {
+			locations: locate.locations,
+		}
+	}
+
+	/// Finds the node with this id in `ast`, which must be the tree the map
+	/// was built from.
+	pub fn get<'a>(&self, ast: &'a Ast, id: NodeId) -> Option<Node<'a>> {
+		let mut path = Vec::new();
+		let mut id = id;
+
+		loop {
+			let (parent, index) = *self.locations.get(&id)?;
+			path.push(index);
+
+			match parent {
+				Some(parent) => id = parent,
+				None => break,
+			}
+		}
+
+		let mut node = Node::Decl(ast.get(path.pop()?)?);
+
+		while let Some(index) = path.pop() {
+			node = *children(node).get(index)?;
+		}
+
+		Some(node)
+	}
+
+	pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+		self.locations.get(&id).and_then(|(parent, _)| *parent)
+	}
+
+	pub fn len(&self) -> usize {
+		self.locations.len()
+	}
+
+	pub fn is_empty(&self) -> bool {
+		self.locations.is_empty()
+	}
+}
+
+/// Numbers every node of the tree in pre-order, starting from zero, and
+/// returns the number of nodes. The parser does this for every tree it
+/// produces; passes that add nodes call it again to keep the ids dense.
+pub fn assign_ids(ast: &mut Ast) -> usize {
+	let mut assign = AssignIds { next: 0 };
+	assign.visit_ast(ast);
+
+	assign.next
+}
+
+/// Maps the id of every node in the tree to the node.
+pub fn node_map(ast: &Ast) -> HashMap<NodeId, Node<'_>> {
+	let mut collect = CollectNodes {
+		nodes: HashMap::new(),
+	};
+	collect.visit_ast(ast);
+
+	collect.nodes
+}
+
+// the nodes directly below `node`, in the order a visitor reaches them
+fn children(node: Node<'_>) -> Vec<Node<'_>> {
+	let mut collect = Children { nodes: Vec::new() };
+
+	match node {
+		Node::Decl(decl) => visit::walk_decl(&mut collect, decl),
+		Node::Stmt(stmt) => visit::walk_stmt(&mut collect, stmt),
+		Node::Expr(expr) => visit::walk_expr(&mut collect, expr),
+		Node::Identifier(_) => (),
+	}
+
+	collect.nodes
+}
+
+struct AssignIds {
+	next: usize,
+}
+
+impl AssignIds {
+	fn next_id(&mut self) -> NodeId {
+		let id = NodeId(self.next);
+		self.next += 1;
+
+		id
+	}
+}
+
+impl VisitorMut for AssignIds {
+	fn visit_decl(&mut self, decl: &mut Decl) {
+		decl.id = self.next_id();
+		visit::walk_decl_mut(self, decl);
+	}
+
+	fn visit_stmt(&mut self, stmt: &mut Stmt) {
+		stmt.id = self.next_id();
+		visit::walk_stmt_mut(self, stmt);
+	}
+
+	fn visit_expr(&mut self, expr: &mut Expr) {
+		expr.id = self.next_id();
+		visit::walk_expr_mut(self, expr);
+	}
+
+	fn visit_identifier(&mut self, identifier: &mut Identifier) {
+		identifier.id = self.next_id();
+	}
+}
+
+struct CollectNodes<'a> {
+	nodes: HashMap<NodeId, Node<'a>>,
+}
+
+impl<'a> CollectNodes<'a> {
+	fn insert(&mut self, node: Node<'a>) {
+		self.nodes.insert(node.id(), node);
+	}
+}
+
+impl<'a> Visitor<'a> for CollectNodes<'a> {
+	fn visit_decl(&mut self, decl: &'a Decl) {
+		self.insert(Node::Decl(decl));
+		visit::walk_decl(self, decl);
+	}
+
+	fn visit_stmt(&mut self, stmt: &'a Stmt) {
+		self.insert(Node::Stmt(stmt));
+		visit::walk_stmt(self, stmt);
+	}
+
+	fn visit_expr(&mut self, expr: &'a Expr) {
+		self.insert(Node::Expr(expr));
+		visit::walk_expr(self, expr);
+	}
+
+	fn visit_identifier(&mut self, identifier: &'a Identifier) {
+		self.insert(Node::Identifier(identifier));
+	}
+}
+
+struct Locate {
+	locations: HashMap<NodeId, (Option<NodeId>, usize)>,
+	// each open node with the number of children seen so far
+	parents: Vec<(NodeId, usize)>,
+	next_decl: usize,
+}
+
+impl Locate {
+	fn enter(&mut self, id: NodeId) {
+		let location = match self.parents.last_mut() {
+			Some((parent, children)) => {
+				*children += 1;
+				(Some(*parent), *children - 1)
+			},
+			None => {
+				self.next_decl += 1;
+				(None, self.next_decl - 1)
+			},
+		};
+
+		self.locations.insert(id, location);
+		self.parents.push((id, 0));
+	}
+
+	fn exit(&mut self) {
+		self.parents.pop();
+	}
+}
+
+impl Visitor<'_> for Locate {
+	fn visit_decl(&mut self, decl: &Decl) {
+		self.enter(decl.id);
+		visit::walk_decl(self, decl);
+		self.exit();
+	}
+
+	fn visit_stmt(&mut self, stmt: &Stmt) {
+		self.enter(stmt.id);
+		visit::walk_stmt(self, stmt);
+		self.exit();
+	}
+
+	fn visit_expr(&mut self, expr: &Expr) {
+		self.enter(expr.id);
+		visit::walk_expr(self, expr);
+		self.exit();
+	}
+
+	fn visit_identifier(&mut self, identifier: &Identifier) {
+		self.enter(identifier.id);
+		self.exit();
+	}
+}
+
+struct Children<'a> {
+	nodes: Vec<Node<'a>>,
+}
+
+impl<'a> Visitor<'a> for Children<'a> {
+	fn visit_decl(&mut self, decl: &'a Decl) {
+		self.nodes.push(Node::Decl(decl));
+	}
+
+	fn visit_stmt(&mut self, stmt: &'a Stmt) {
+		self.nodes.push(Node::Stmt(stmt));
+	}
+
+	fn visit_expr(&mut self, expr: &'a Expr) {
+		self.nodes.push(Node::Expr(expr));
+	}
+
+	fn visit_identifier(&mut self, identifier: &'a Identifier) {
+		self.nodes.push(Node::Identifier(identifier));
+	}
+}
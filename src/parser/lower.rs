@@ -0,0 +1,43 @@
+use crate::parser::ast::*;
+use crate::parser::fold::{self, Fold};
+use crate::parser::ids::assign_ids;
+
+pub fn lower_operators(ast: Ast) -> Ast {
+	let mut ast = LowerOperators.fold_ast(ast);
+	assign_ids(&mut ast);
+
+	ast
+}
+
+// rewrites every operator application into a call of the operator
+struct LowerOperators;
+
+impl Fold for LowerOperators {
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		let Expr { id, kind, span } = fold::fold_expr(self, expr);
+
+		let kind = match kind {
+			ExprKind::Unary(un_op, operand) => {
+				let func = Expr {
+					id: NodeId::DUMMY,
+					kind: ExprKind::Identifier(un_op.lexeme),
+					span: un_op.span,
+				};
+
+				ExprKind::Call(Box::new(func), vec![*operand])
+			},
+			ExprKind::Binary(bin_op, lhs, rhs) => {
+				let func = Expr {
+					id: NodeId::DUMMY,
+					kind: ExprKind::Identifier(bin_op.lexeme),
+					span: bin_op.span,
+				};
+
+				ExprKind::Call(Box::new(func), vec![*lhs, *rhs])
+			},
+			kind => kind,
+		};
+
+		Expr { id, kind, span }
+	}
+}
@@ -0,0 +1,475 @@
+use std::ops::Range;
+
+use indoc::indoc;
+
+use crate::parser::arena;
+use crate::parser::ast::*;
+use crate::parser::ids::node_map;
+use crate::parser::lower::lower_operators;
+use crate::parser::Parser;
+
+fn shape(expr: &Expr) -> String {
+	match &expr.kind {
+		ExprKind::Integer(i) => i.to_string(),
+		ExprKind::Identifier(i) => i.clone(),
+		ExprKind::Call(func, args) => {
+			let args = args.iter().map(shape).collect::<Vec<_>>();
+			format!("{}({})", shape(func), args.join(", "))
+		},
+		ExprKind::Binary(bin_op, lhs, rhs) => {
+			format!("({} {} {})", shape(lhs), bin_op.lexeme, shape(rhs))
+		},
+		ExprKind::Unary(un_op, operand) => format!("({}{})", un_op.lexeme, shape(operand)),
+		ExprKind::Error => String::from("<error>"),
+		kind => panic!("unexpected expression: {kind:?}"),
+	}
+}
+
+fn parse_expr(source: &str) -> String {
+	let source = format!("f() = {source}");
+	let ast = Parser::new(&source).parse().unwrap();
+
+	let DeclKind::Fn(fn_def) = &ast[0].kind else {
+		panic!("expected function definition");
+	};
+
+	shape(&fn_def.body)
+}
+
+#[test]
+fn binary_precedence() {
+	let cases = [
+		("a || b && c", "(a || (b && c))"),
+		("a && b || c", "((a && b) || c)"),
+		("a == b && c != d", "((a == b) && (c != d))"),
+		("a < b != c >= d", "((a < b) != (c >= d))"),
+		("a + b < c * d", "((a + b) < (c * d))"),
+		("a - b - c", "((a - b) - c)"),
+		("a * b % c / d", "(((a * b) % c) / d)"),
+		("a + b * c ** d ** e", "(a + (b * (c ** (d ** e))))"),
+		("a <> b + c", "((a <> b) + c)"),
+		(
+			"a || b && c == d + e * f ** g % h",
+			"(a || (b && (c == (d + ((e * (f ** g)) % h)))))",
+		),
+	];
+
+	for (source, expected) in cases {
+		assert_eq!(parse_expr(source), expected, "{source}");
+	}
+}
+
+#[test]
+fn fixity_declarations() {
+	let source = indoc! {"
+		f() = a <> b <> c + d
+		g() = x |> h ** y |> k
+		infixr 5 <>
+		infixl 1 |> **
+		h() = a + b * c"
+	};
+
+	let ast = Parser::new(source).parse().unwrap();
+
+	let bodies: Vec<_> = ast
+		.iter()
+		.filter_map(|decl| match &decl.kind {
+			DeclKind::Fn(fn_def) => Some(shape(&fn_def.body)),
+			_ => None,
+		})
+		.collect();
+
+	let expected = vec![
+		"((a <> (b <> c)) + d)",
+		"(((x |> h) ** y) |> k)",
+		"(a + (b * c))",
+	];
+
+	assert_eq!(bodies, expected);
+}
+
+#[test]
+fn invalid_fixity_declarations() {
+	let cases = [
+		("infixl 0 <>", "invalid precedence"),
+		("infixl 10 <>", "invalid precedence"),
+		("infixr x <>", "invalid precedence"),
+		("infixr 3 x", "invalid operator"),
+	];
+
+	for (source, title) in cases {
+		let err = Parser::new(source).parse().unwrap_err();
+		assert_eq!(err.title, title, "{source}");
+	}
+}
+
+#[test]
+fn unary_precedence() {
+	let cases = [
+		("-a + b", "((-a) + b)"),
+		("-a * b", "((-a) * b)"),
+		("a * -b", "(a * (-b))"),
+		("-a ** 2", "(-(a ** 2))"),
+		("a ** -b ** c", "(a ** (-(b ** c)))"),
+		("!a && b", "((!a) && b)"),
+		("!a == b", "((!a) == b)"),
+		("- -a - b", "((-(-a)) - b)"),
+		("-f(x) + 1", "((-f(x)) + 1)"),
+		("-(a + b)", "(-(a + b))"),
+	];
+
+	for (source, expected) in cases {
+		assert_eq!(parse_expr(source), expected, "{source}");
+	}
+}
+
+#[test]
+fn lower_operators_to_calls() {
+	let source = "f() = -a + g(b * c, !d)";
+	let ast = lower_operators(Parser::new(source).parse().unwrap());
+
+	let DeclKind::Fn(fn_def) = &ast[0].kind else {
+		panic!("expected function definition");
+	};
+
+	assert_eq!(shape(&fn_def.body), "+(-(a), g(*(b, c), !(d)))");
+}
+
+#[test]
+fn recover_from_errors() {
+	let source = indoc! {"
+		f() = a +
+		g(x) = x
+		h: (Int
+		k() =
+		  a = )
+		  b
+		  c d
+		  e
+		m = 1
+		n() = 2"
+	};
+
+	let (ast, errors) = Parser::new(source).parse_recovering();
+
+	let titles: Vec<_> = errors.iter().map(|err| err.title.as_str()).collect();
+	let expected = vec![
+		"invalid expression",
+		"invalid type",
+		"invalid expression",
+		"invalid infix expression",
+		"invalid declaration",
+	];
+
+	assert_eq!(titles, expected);
+
+	let decls: Vec<_> = ast
+		.iter()
+		.map(|decl| match &decl.kind {
+			DeclKind::Fn(fn_def) => fn_def.func.name.as_str(),
+			DeclKind::Error => "<error>",
+			_ => "<other>",
+		})
+		.collect();
+
+	assert_eq!(decls, vec!["<error>", "g", "<error>", "k", "<error>", "n"]);
+
+	let DeclKind::Fn(fn_def) = &ast[3].kind else {
+		unreachable!();
+	};
+
+	let ExprKind::Block(stmts) = &fn_def.body.kind else {
+		panic!("expected block");
+	};
+
+	let stmts: Vec<_> = stmts
+		.iter()
+		.map(|stmt| match &stmt.kind {
+			StmtKind::Expr(expr) => shape(expr),
+			_ => String::from("<other>"),
+		})
+		.collect();
+
+	assert_eq!(stmts, vec!["<error>", "b", "<error>", "e"]);
+
+	let first_error = Parser::new(source).parse().unwrap_err();
+	assert_eq!(first_error.title, errors[0].title);
+	assert_eq!(first_error.labels, errors[0].labels);
+}
+
+#[test]
+fn skip_lexer_errors() {
+	let source = indoc! {"
+		f() = a ` + 0b2
+		g(x) =
+		  y = \"abc
+		  y ~ x
+		h() = 1"
+	};
+
+	let (ast, errors) = Parser::new(source).parse_recovering();
+
+	let titles: Vec<_> = errors.iter().map(|err| err.title.as_str()).collect();
+	assert_eq!(
+		titles,
+		vec![
+			"unrecognized character",
+			"invalid digit",
+			"unterminated string"
+		]
+	);
+
+	let bodies: Vec<_> = ast
+		.iter()
+		.map(|decl| match &decl.kind {
+			DeclKind::Fn(FnDef {
+				body: Expr {
+					kind: ExprKind::Block(stmts),
+					..
+				},
+				..
+			}) => stmts.len().to_string(),
+			DeclKind::Fn(fn_def) => shape(&fn_def.body),
+			_ => String::from("<other>"),
+		})
+		.collect();
+
+	assert_eq!(bodies, vec!["(a + <error>)", "2", "1"]);
+}
+
+#[test]
+fn recover_inside_call_args() {
+	let source = indoc! {"
+		f() =
+		  g(a, if c then
+		    h(b, ,)
+		  else 0)
+	"};
+
+	let (ast, errors) = Parser::new(source).parse_recovering();
+
+	assert_eq!(errors.len(), 1);
+
+	let DeclKind::Fn(FnDef {
+		body: Expr {
+			kind: ExprKind::Block(stmts),
+			..
+		},
+		..
+	}) = &ast[0].kind
+	else {
+		panic!("expected block");
+	};
+	let StmtKind::Expr(Expr {
+		kind: ExprKind::Call(_, args),
+		..
+	}) = &stmts[0].kind
+	else {
+		panic!("expected call");
+	};
+
+	// the arguments of the failed inner call must not leak into `g`
+	assert_eq!(args.len(), 2);
+	assert_eq!(shape(&args[0]), "a");
+	assert!(matches!(args[1].kind, ExprKind::If(..)));
+}
+
+#[test]
+fn doc_comments_attach_to_declarations() {
+	let source = indoc! {"
+		## combines
+		infixl 6 <+>
+		## one
+		f() = 1 ## trailing note on f
+		g() =
+		  ## not for a variable
+		  x = 1
+		  ## a type
+		  y: Int
+		  x
+	"};
+
+	let mut parser = Parser::new(source);
+	let ast = parser.parse().unwrap();
+
+	let texts =
+		|decl: &Decl| -> Vec<String> { decl.docs().iter().map(|doc| doc.text.clone()).collect() };
+
+	assert_eq!(texts(&ast[0]), vec!["combines"]);
+	assert_eq!(texts(&ast[1]), vec!["one"]);
+	assert!(ast[2].docs().is_empty());
+
+	let DeclKind::Fn(FnDef {
+		body: Expr {
+			kind: ExprKind::Block(stmts),
+			..
+		},
+		..
+	}) = &ast[2].kind
+	else {
+		panic!("expected block");
+	};
+	let StmtKind::TyDecl(ty_decl) = &stmts[1].kind else {
+		panic!("expected type declaration");
+	};
+
+	assert_eq!(ty_decl.docs[0].text, "a type");
+	assert!(parser.lexer.take_docs(stmts[0].span).is_empty());
+}
+
+#[test]
+fn spans_on_multi_byte_source() {
+	let source = "f() = \"é\" + x\ng() = h('λ', \"ü\")";
+	let ast = Parser::new(source).parse().unwrap();
+
+	let mut spans: Vec<_> = node_map(&ast).into_values().collect();
+	spans.sort_by_key(|node| node.id());
+
+	let lexemes: Vec<_> = spans
+		.iter()
+		.map(|node| &source[Range::from(node.span())])
+		.collect();
+
+	assert_eq!(
+		lexemes,
+		[
+			"f() = \"é\" + x",
+			"f",
+			"\"é\" + x",
+			"\"é\"",
+			"x",
+			"g() = h('λ', \"ü\")",
+			"g",
+			"h('λ', \"ü\")",
+			"h",
+			"'λ'",
+			"\"ü\"",
+		]
+	);
+}
+
+#[test]
+fn structural_equality() {
+	let parse = |source| Parser::new(source).parse().unwrap();
+
+	let a = parse("## abs\nf(x) = if x < 0 then -x else (x)\n");
+	let b = parse("## abs\nf( x )=if (x<0) then - x else x");
+
+	assert_ne!(a, b);
+	assert!(ast_eq_ignoring_spans(&a, &b));
+	assert_eq!(a.clone(), a);
+
+	let c = parse("f(x) = if x < 0 then -x else 0.0");
+	let d = parse("f(x) = if x < 0 then -x else 0.0");
+
+	assert_eq!(c, d);
+	assert!(!ast_eq_ignoring_spans(&a, &c));
+
+	// the same declaration numbered from a different start
+	let e = parse("g() = 0\nf(x) = if x < 0 then -x else 0.0")[1..].to_vec();
+
+	assert_ne!(c, e);
+	assert!(ast_eq_ignoring_spans(&c, &e));
+
+	// floats compare by bits so the tree stays reflexive
+	assert_eq!(ExprKind::Float(f64::NAN), ExprKind::Float(f64::NAN));
+	assert_ne!(ExprKind::Float(0.0), ExprKind::Float(-0.0));
+}
+
+#[test]
+fn pre_order_node_ids() {
+	let source = indoc! {"
+		f(x) = g(x) + 1
+		g(y) =
+		  z = y
+		  z
+	"};
+	let (ast, map) = Parser::new(source).parse_with_nodes().unwrap();
+	let nodes = node_map(&ast);
+
+	let mut order: Vec<_> = nodes.values().collect();
+	order.sort_by_key(|node| node.id());
+
+	let order: Vec<_> = order
+		.iter()
+		.map(|node| &source[Range::from(node.span())])
+		.collect();
+
+	assert_eq!(
+		order,
+		[
+			"f(x) = g(x) + 1",
+			"f",
+			"x",
+			"g(x) + 1",
+			"g(x)",
+			"g",
+			"x",
+			"1",
+			"g(y) =\n  z = y\n  z\n",
+			"g",
+			"y",
+			"z = y\n  z\n",
+			"z = y",
+			"z",
+			"y",
+			"z",
+			"z",
+		]
+	);
+	assert!(nodes.keys().all(|id| id.0 < nodes.len()));
+
+	assert_eq!(map.len(), nodes.len());
+	assert!(nodes
+		.iter()
+		.all(|(id, node)| map.get(&ast, *id) == Some(*node)));
+	assert_eq!(map.get(&ast, NodeId(nodes.len())), None);
+
+	// `y` in `z = y` sits below the variable definition statement
+	assert_eq!(map.parent(NodeId(14)), Some(NodeId(12)));
+	assert_eq!(map.parent(NodeId(0)), None);
+
+	let lowered = lower_operators(ast.clone());
+	assert_eq!(node_map(&lowered).len(), nodes.len() + 1);
+	assert!(node_map(&lowered).keys().all(|id| *id != NodeId::DUMMY));
+}
+
+#[test]
+fn arena_layout() {
+	let source = "f() = g(1, h((2)), 3)";
+	let (ast, errors) = Parser::new_arena(source).parse_arena();
+
+	assert!(errors.is_empty());
+
+	let arena::DeclKind::Fn(fn_def) = &ast.decls[0].kind else {
+		panic!("expected function definition");
+	};
+	let arena::ExprKind::Call(_, args) = &ast.arena[fn_def.body].kind else {
+		panic!("expected call");
+	};
+	let arena::ExprKind::Call(_, inner) = &ast.arena[ast.arena[*args][1]].kind else {
+		panic!("expected call");
+	};
+
+	assert_eq!(args.len(), 3);
+	assert_eq!(ast.arena[ast.arena[*inner][0]].span.to_string(), "13..16");
+
+	// parentheses reuse the inner node, so every expression is reachable
+	let exprs = ast.arena.exprs().len();
+	let ast = ast.into_ast();
+
+	assert_eq!(exprs, 7);
+
+	let DeclKind::Fn(fn_def) = &ast[0].kind else {
+		unreachable!();
+	};
+	let ids: Vec<_> = node_map(&ast).keys().map(|id| id.0).collect();
+
+	assert_eq!(shape(&fn_def.body), "g(1, h(2), 3)");
+	assert_eq!(fn_def.body.id, NodeId(2));
+	assert_eq!(ids.len(), 9);
+	assert!(ids.iter().all(|id| *id < 9));
+
+	// both builders agree on every node, span and id
+	assert_eq!(ast, Parser::new(source).parse().unwrap());
+}
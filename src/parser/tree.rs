@@ -0,0 +1,247 @@
+use crate::common::span::Span;
+use crate::parser::ast::*;
+use crate::parser::ids::assign_ids;
+
+/// Builds the tree a [`Parser`](crate::parser::Parser) produces. The parser
+/// only creates nodes through these methods, so [`Boxed`] builds the boxed
+/// [`Ast`] and [`Arena`](crate::parser::arena::Arena) builds an
+/// [`ArenaAst`](crate::parser::arena::ArenaAst) directly, without converting
+/// from one to the other.
+pub trait TreeBuilder: Default {
+	type Expr;
+	type Stmt;
+	type Decl;
+	type Output;
+
+	fn leaf(&mut self, leaf: Leaf, span: Span) -> Self::Expr;
+
+	fn if_expr(
+		&mut self,
+		cond: Self::Expr,
+		then_expr: Self::Expr,
+		else_expr: Option<Self::Expr>,
+		span: Span,
+	) -> Self::Expr;
+
+	fn while_expr(&mut self, cond: Self::Expr, body: Self::Expr, span: Span) -> Self::Expr;
+
+	fn call(
+		&mut self,
+		func: Self::Expr,
+		args: impl IntoIterator<Item = Self::Expr>,
+		span: Span,
+	) -> Self::Expr;
+
+	fn unary(&mut self, un_op: UnaryOp, operand: Self::Expr, span: Span) -> Self::Expr;
+
+	fn binary(
+		&mut self,
+		bin_op: BinaryOp,
+		lhs: Self::Expr,
+		rhs: Self::Expr,
+		span: Span,
+	) -> Self::Expr;
+
+	fn block(&mut self, stmts: impl IntoIterator<Item = Self::Stmt>, span: Span) -> Self::Expr;
+
+	fn span(&self, expr: &Self::Expr) -> Span;
+
+	fn set_span(&mut self, expr: &mut Self::Expr, span: Span);
+
+	fn ty_decl_stmt(&mut self, ty_decl: TyDecl) -> Self::Stmt;
+
+	fn var_def(&mut self, var: Identifier, value: Self::Expr, span: Span) -> Self::Stmt;
+
+	fn expr_stmt(&mut self, expr: Self::Expr, span: Span) -> Self::Stmt;
+
+	fn ty_decl(&mut self, ty_decl: TyDecl) -> Self::Decl;
+
+	fn fn_def(
+		&mut self,
+		func: Identifier,
+		params: Vec<Identifier>,
+		body: Self::Expr,
+		docs: Vec<DocComment>,
+		span: Span,
+	) -> Self::Decl;
+
+	fn fixity_decl(&mut self, fixity_decl: FixityDecl) -> Self::Decl;
+
+	fn error_decl(&mut self, span: Span) -> Self::Decl;
+
+	fn as_fixity_decl(decl: &Self::Decl) -> Option<&FixityDecl>;
+
+	fn finish(self, decls: Vec<Self::Decl>) -> Self::Output;
+}
+
+/// An expression without children.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Leaf {
+	Boolean(bool),
+	Integer(i64),
+	Float(f64),
+	String(String),
+	Char(char),
+	Identifier(String),
+	Error,
+}
+
+/// Builds the boxed [`Ast`], numbering its nodes once parsing is done.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Boxed;
+
+impl Boxed {
+	fn expr(&self, kind: ExprKind, span: Span) -> Expr {
+		Expr {
+			id: NodeId::DUMMY,
+			kind,
+			span,
+		}
+	}
+
+	fn stmt(&self, kind: StmtKind, span: Span) -> Stmt {
+		Stmt {
+			id: NodeId::DUMMY,
+			kind,
+			span,
+		}
+	}
+
+	fn decl(&self, kind: DeclKind, span: Span) -> Decl {
+		Decl {
+			id: NodeId::DUMMY,
+			kind,
+			span,
+		}
+	}
+}
+
+impl TreeBuilder for Boxed {
+	type Expr = Expr;
+	type Stmt = Stmt;
+	type Decl = Decl;
+	type Output = Ast;
+
+	fn leaf(&mut self, leaf: Leaf, span: Span) -> Expr {
+		let kind = match leaf {
+			Leaf::Boolean(b) => ExprKind::Boolean(b),
+			Leaf::Integer(i) => ExprKind::Integer(i),
+			Leaf::Float(f) => ExprKind::Float(f),
+			Leaf::String(s) => ExprKind::String(s),
+			Leaf::Char(c) => ExprKind::Char(c),
+			Leaf::Identifier(i) => ExprKind::Identifier(i),
+			Leaf::Error => ExprKind::Error,
+		};
+
+		self.expr(kind, span)
+	}
+
+	fn if_expr(
+		&mut self,
+		cond: Expr,
+		then_expr: Expr,
+		else_expr: Option<Expr>,
+		span: Span,
+	) -> Expr {
+		let kind = ExprKind::If(Box::new(cond), Box::new(then_expr), else_expr.map(Box::new));
+
+		self.expr(kind, span)
+	}
+
+	fn while_expr(&mut self, cond: Expr, body: Expr, span: Span) -> Expr {
+		self.expr(ExprKind::While(Box::new(cond), Box::new(body)), span)
+	}
+
+	fn call(&mut self, func: Expr, args: impl IntoIterator<Item = Expr>, span: Span) -> Expr {
+		let kind = ExprKind::Call(Box::new(func), args.into_iter().collect());
+
+		self.expr(kind, span)
+	}
+
+	fn unary(&mut self, un_op: UnaryOp, operand: Expr, span: Span) -> Expr {
+		self.expr(ExprKind::Unary(un_op, Box::new(operand)), span)
+	}
+
+	fn binary(&mut self, bin_op: BinaryOp, lhs: Expr, rhs: Expr, span: Span) -> Expr {
+		self.expr(ExprKind::Binary(bin_op, Box::new(lhs), Box::new(rhs)), span)
+	}
+
+	fn block(&mut self, stmts: impl IntoIterator<Item = Stmt>, span: Span) -> Expr {
+		self.expr(ExprKind::Block(stmts.into_iter().collect()), span)
+	}
+
+	fn span(&self, expr: &Expr) -> Span {
+		expr.span
+	}
+
+	fn set_span(&mut self, expr: &mut Expr, span: Span) {
+		expr.span = span;
+	}
+
+	fn ty_decl_stmt(&mut self, ty_decl: TyDecl) -> Stmt {
+		let span = ty_decl.span;
+
+		self.stmt(StmtKind::TyDecl(ty_decl), span)
+	}
+
+	fn var_def(&mut self, var: Identifier, value: Expr, span: Span) -> Stmt {
+		let var_def = VarDef { var, value, span };
+
+		self.stmt(StmtKind::VarDef(var_def), span)
+	}
+
+	fn expr_stmt(&mut self, expr: Expr, span: Span) -> Stmt {
+		self.stmt(StmtKind::Expr(expr), span)
+	}
+
+	fn ty_decl(&mut self, ty_decl: TyDecl) -> Decl {
+		let span = ty_decl.span;
+
+		self.decl(DeclKind::Ty(ty_decl), span)
+	}
+
+	fn fn_def(
+		&mut self,
+		func: Identifier,
+		params: Vec<Identifier>,
+		body: Expr,
+		docs: Vec<DocComment>,
+		span: Span,
+	) -> Decl {
+		let fn_def = FnDef {
+			func,
+			params,
+			body,
+			docs,
+			span,
+		};
+
+		self.decl(DeclKind::Fn(fn_def), span)
+	}
+
+	fn fixity_decl(&mut self, fixity_decl: FixityDecl) -> Decl {
+		let span = fixity_decl.span;
+
+		self.decl(DeclKind::Fixity(fixity_decl), span)
+	}
+
+	fn error_decl(&mut self, span: Span) -> Decl {
+		self.decl(DeclKind::Error, span)
+	}
+
+	fn as_fixity_decl(decl: &Decl) -> Option<&FixityDecl> {
+		match &decl.kind {
+			DeclKind::Fixity(fixity_decl) => Some(fixity_decl),
+			_ => None,
+		}
+	}
+
+	// children are built before their parents, so pre-order ids can only be
+	// given out once the whole tree exists
+	fn finish(self, decls: Vec<Decl>) -> Ast {
+		let mut ast = decls;
+		assign_ids(&mut ast);
+
+		ast
+	}
+}
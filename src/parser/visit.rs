@@ -0,0 +1,302 @@
+use crate::parser::ast::*;
+
+/// Traverses an AST by reference. Every method defaults to the matching `walk_`
+/// function, which visits the children of a node, so an implementation only
+/// overrides the nodes it is interested in and calls `walk_` to keep going.
+/// References live as long as the tree, so a visitor may keep them.
+pub trait Visitor<'a> {
+	fn visit_ast(&mut self, ast: &'a Ast) {
+		walk_ast(self, ast);
+	}
+
+	fn visit_decl(&mut self, decl: &'a Decl) {
+		walk_decl(self, decl);
+	}
+
+	fn visit_ty_decl(&mut self, ty_decl: &'a TyDecl) {
+		walk_ty_decl(self, ty_decl);
+	}
+
+	fn visit_fn_def(&mut self, fn_def: &'a FnDef) {
+		walk_fn_def(self, fn_def);
+	}
+
+	fn visit_fixity_decl(&mut self, fixity_decl: &'a FixityDecl) {
+		walk_fixity_decl(self, fixity_decl);
+	}
+
+	fn visit_stmt(&mut self, stmt: &'a Stmt) {
+		walk_stmt(self, stmt);
+	}
+
+	fn visit_var_def(&mut self, var_def: &'a VarDef) {
+		walk_var_def(self, var_def);
+	}
+
+	fn visit_expr(&mut self, expr: &'a Expr) {
+		walk_expr(self, expr);
+	}
+
+	fn visit_ty(&mut self, ty: &'a Ty) {
+		walk_ty(self, ty);
+	}
+
+	fn visit_identifier(&mut self, _identifier: &'a Identifier) {}
+}
+
+pub fn walk_ast<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, ast: &'a Ast) {
+	for decl in ast {
+		visitor.visit_decl(decl);
+	}
+}
+
+pub fn walk_decl<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, decl: &'a Decl) {
+	match &decl.kind {
+		DeclKind::Ty(ty_decl) => visitor.visit_ty_decl(ty_decl),
+		DeclKind::Fn(fn_def) => visitor.visit_fn_def(fn_def),
+		DeclKind::Fixity(fixity_decl) => visitor.visit_fixity_decl(fixity_decl),
+		DeclKind::Error => (),
+	}
+}
+
+pub fn walk_ty_decl<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, ty_decl: &'a TyDecl) {
+	visitor.visit_identifier(&ty_decl.identifier);
+	visitor.visit_ty(&ty_decl.ty);
+}
+
+pub fn walk_fn_def<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, fn_def: &'a FnDef) {
+	visitor.visit_identifier(&fn_def.func);
+
+	for param in &fn_def.params {
+		visitor.visit_identifier(param);
+	}
+
+	visitor.visit_expr(&fn_def.body);
+}
+
+pub fn walk_fixity_decl<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, fixity_decl: &'a FixityDecl) {
+	for op in &fixity_decl.ops {
+		visitor.visit_identifier(op);
+	}
+}
+
+pub fn walk_stmt<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, stmt: &'a Stmt) {
+	match &stmt.kind {
+		StmtKind::TyDecl(ty_decl) => visitor.visit_ty_decl(ty_decl),
+		StmtKind::VarDef(var_def) => visitor.visit_var_def(var_def),
+		StmtKind::Expr(expr) => visitor.visit_expr(expr),
+	}
+}
+
+pub fn walk_var_def<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, var_def: &'a VarDef) {
+	visitor.visit_identifier(&var_def.var);
+	visitor.visit_expr(&var_def.value);
+}
+
+pub fn walk_expr<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, expr: &'a Expr) {
+	match &expr.kind {
+		ExprKind::Boolean(_)
+		| ExprKind::Integer(_)
+		| ExprKind::Float(_)
+		| ExprKind::String(_)
+		| ExprKind::Char(_)
+		| ExprKind::Identifier(_)
+		| ExprKind::Error => (),
+		ExprKind::If(cond, then_expr, else_expr) => {
+			visitor.visit_expr(cond);
+			visitor.visit_expr(then_expr);
+
+			if let Some(else_expr) = else_expr {
+				visitor.visit_expr(else_expr);
+			}
+		},
+		ExprKind::While(cond, body) => {
+			visitor.visit_expr(cond);
+			visitor.visit_expr(body);
+		},
+		ExprKind::Call(func, args) => {
+			visitor.visit_expr(func);
+
+			for arg in args {
+				visitor.visit_expr(arg);
+			}
+		},
+		ExprKind::Unary(_, operand) => visitor.visit_expr(operand),
+		ExprKind::Binary(_, lhs, rhs) => {
+			visitor.visit_expr(lhs);
+			visitor.visit_expr(rhs);
+		},
+		ExprKind::Block(stmts) => {
+			for stmt in stmts {
+				visitor.visit_stmt(stmt);
+			}
+		},
+	}
+}
+
+pub fn walk_ty<'a, V: Visitor<'a> + ?Sized>(visitor: &mut V, ty: &'a Ty) {
+	match &ty.kind {
+		TyKind::Single(_) => (),
+		TyKind::Tuple(tys) => {
+			for ty in tys {
+				visitor.visit_ty(ty);
+			}
+		},
+		TyKind::Function(params, ret) => {
+			for param in params {
+				visitor.visit_ty(param);
+			}
+
+			visitor.visit_ty(ret);
+		},
+	}
+}
+
+/// Traverses an AST by mutable reference, in the same order as [`Visitor`].
+pub trait VisitorMut {
+	fn visit_ast(&mut self, ast: &mut Ast) {
+		walk_ast_mut(self, ast);
+	}
+
+	fn visit_decl(&mut self, decl: &mut Decl) {
+		walk_decl_mut(self, decl);
+	}
+
+	fn visit_ty_decl(&mut self, ty_decl: &mut TyDecl) {
+		walk_ty_decl_mut(self, ty_decl);
+	}
+
+	fn visit_fn_def(&mut self, fn_def: &mut FnDef) {
+		walk_fn_def_mut(self, fn_def);
+	}
+
+	fn visit_fixity_decl(&mut self, fixity_decl: &mut FixityDecl) {
+		walk_fixity_decl_mut(self, fixity_decl);
+	}
+
+	fn visit_stmt(&mut self, stmt: &mut Stmt) {
+		walk_stmt_mut(self, stmt);
+	}
+
+	fn visit_var_def(&mut self, var_def: &mut VarDef) {
+		walk_var_def_mut(self, var_def);
+	}
+
+	fn visit_expr(&mut self, expr: &mut Expr) {
+		walk_expr_mut(self, expr);
+	}
+
+	fn visit_ty(&mut self, ty: &mut Ty) {
+		walk_ty_mut(self, ty);
+	}
+
+	fn visit_identifier(&mut self, _identifier: &mut Identifier) {}
+}
+
+pub fn walk_ast_mut<V: VisitorMut + ?Sized>(visitor: &mut V, ast: &mut Ast) {
+	for decl in ast {
+		visitor.visit_decl(decl);
+	}
+}
+
+pub fn walk_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, decl: &mut Decl) {
+	match &mut decl.kind {
+		DeclKind::Ty(ty_decl) => visitor.visit_ty_decl(ty_decl),
+		DeclKind::Fn(fn_def) => visitor.visit_fn_def(fn_def),
+		DeclKind::Fixity(fixity_decl) => visitor.visit_fixity_decl(fixity_decl),
+		DeclKind::Error => (),
+	}
+}
+
+pub fn walk_ty_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, ty_decl: &mut TyDecl) {
+	visitor.visit_identifier(&mut ty_decl.identifier);
+	visitor.visit_ty(&mut ty_decl.ty);
+}
+
+pub fn walk_fn_def_mut<V: VisitorMut + ?Sized>(visitor: &mut V, fn_def: &mut FnDef) {
+	visitor.visit_identifier(&mut fn_def.func);
+
+	for param in &mut fn_def.params {
+		visitor.visit_identifier(param);
+	}
+
+	visitor.visit_expr(&mut fn_def.body);
+}
+
+pub fn walk_fixity_decl_mut<V: VisitorMut + ?Sized>(visitor: &mut V, fixity_decl: &mut FixityDecl) {
+	for op in &mut fixity_decl.ops {
+		visitor.visit_identifier(op);
+	}
+}
+
+pub fn walk_stmt_mut<V: VisitorMut + ?Sized>(visitor: &mut V, stmt: &mut Stmt) {
+	match &mut stmt.kind {
+		StmtKind::TyDecl(ty_decl) => visitor.visit_ty_decl(ty_decl),
+		StmtKind::VarDef(var_def) => visitor.visit_var_def(var_def),
+		StmtKind::Expr(expr) => visitor.visit_expr(expr),
+	}
+}
+
+pub fn walk_var_def_mut<V: VisitorMut + ?Sized>(visitor: &mut V, var_def: &mut VarDef) {
+	visitor.visit_identifier(&mut var_def.var);
+	visitor.visit_expr(&mut var_def.value);
+}
+
+pub fn walk_expr_mut<V: VisitorMut + ?Sized>(visitor: &mut V, expr: &mut Expr) {
+	match &mut expr.kind {
+		ExprKind::Boolean(_)
+		| ExprKind::Integer(_)
+		| ExprKind::Float(_)
+		| ExprKind::String(_)
+		| ExprKind::Char(_)
+		| ExprKind::Identifier(_)
+		| ExprKind::Error => (),
+		ExprKind::If(cond, then_expr, else_expr) => {
+			visitor.visit_expr(cond);
+			visitor.visit_expr(then_expr);
+
+			if let Some(else_expr) = else_expr {
+				visitor.visit_expr(else_expr);
+			}
+		},
+		ExprKind::While(cond, body) => {
+			visitor.visit_expr(cond);
+			visitor.visit_expr(body);
+		},
+		ExprKind::Call(func, args) => {
+			visitor.visit_expr(func);
+
+			for arg in args {
+				visitor.visit_expr(arg);
+			}
+		},
+		ExprKind::Unary(_, operand) => visitor.visit_expr(operand),
+		ExprKind::Binary(_, lhs, rhs) => {
+			visitor.visit_expr(lhs);
+			visitor.visit_expr(rhs);
+		},
+		ExprKind::Block(stmts) => {
+			for stmt in stmts {
+				visitor.visit_stmt(stmt);
+			}
+		},
+	}
+}
+
+pub fn walk_ty_mut<V: VisitorMut + ?Sized>(visitor: &mut V, ty: &mut Ty) {
+	match &mut ty.kind {
+		TyKind::Single(_) => (),
+		TyKind::Tuple(tys) => {
+			for ty in tys {
+				visitor.visit_ty(ty);
+			}
+		},
+		TyKind::Function(params, ret) => {
+			for param in params {
+				visitor.visit_ty(param);
+			}
+
+			visitor.visit_ty(ret);
+		},
+	}
+}
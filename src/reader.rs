@@ -1,26 +1,25 @@
 use std::iter::Peekable;
-use std::vec::IntoIter;
+use std::str::CharIndices;
 
-pub struct Reader {
+pub struct Reader<'a> {
 	pub index: usize,
 	pub ln: usize,
 	pub col: usize,
-	chars: Peekable<IntoIter<char>>,
+	pub line_start: usize,
+	source: &'a str,
+	chars: Peekable<CharIndices<'a>>,
 }
 
-impl Reader {
-	pub fn new(source: &str) -> Self {
-		// TODO: optimize memory usage
-		let chars = String::from(source).chars().collect::<Vec<_>>();
-
-		let reader = Self {
+impl<'a> Reader<'a> {
+	pub fn new(source: &'a str) -> Self {
+		Self {
 			index: 0,
 			ln: 1,
 			col: 1,
-			chars: chars.into_iter().peekable(),
-		};
-
-		reader
+			line_start: 0,
+			source,
+			chars: source.char_indices().peekable(),
+		}
 	}
 
 	pub fn skip_whitespace(&mut self, include_newline: bool) {
@@ -39,25 +38,37 @@ impl Reader {
 
 	pub fn peek(&mut self) -> char {
 		match self.chars.peek() {
-			Some(c) => c.clone(),
+			Some((_, c)) => *c,
 			None => '\0',
 		}
 	}
 
+	// the character after the one `peek` returns
+	pub fn peek_second(&self) -> char {
+		self.source[self.index..].chars().nth(1).unwrap_or('\0')
+	}
+
+	pub fn slice(&self, start: usize, end: usize) -> &'a str {
+		&self.source[start..end]
+	}
+
 	pub fn next(&mut self) -> char {
 		let c = self.peek();
 		self.chars.next();
 
+		self.index = match self.chars.peek() {
+			Some((i, _)) => *i,
+			None => self.source.len(),
+		};
+
 		if c == '\n' {
 			self.ln += 1;
 			self.col = 1;
+			self.line_start = self.index;
 		} else {
 			self.col += 1;
 		}
 
-		// TODO: change this to account for codepoint sizes, if necessary
-		self.index += 1;
-
 		c
 	}
 
@@ -0,0 +1,66 @@
+use std::io::Write;
+use std::process::{Command, Output, Stdio};
+
+fn run(args: &[&str], stdin: &str) -> Output {
+	let mut child = Command::new(env!("CARGO_BIN_EXE_rosy-parse"))
+		.args(args)
+		.current_dir(env!("CARGO_MANIFEST_DIR"))
+		.stdin(Stdio::piped())
+		.stdout(Stdio::piped())
+		.stderr(Stdio::piped())
+		.spawn()
+		.unwrap();
+
+	child
+		.stdin
+		.take()
+		.unwrap()
+		.write_all(stdin.as_bytes())
+		.unwrap();
+
+	child.wait_with_output().unwrap()
+}
+
+#[test]
+fn check_sets_exit_code() {
+	let output = run(&["check", "tests/corpus/basics.rosy", "-"], "f() = 1\n");
+	assert_eq!(output.status.code(), Some(0));
+	assert!(output.stderr.is_empty());
+
+	let output = run(&["check", "tests/corpus/basics.rosy", "-"], "f() = 1 `\n");
+	let stderr = String::from_utf8(output.stderr).unwrap();
+
+	assert_eq!(output.status.code(), Some(1));
+	assert!(stderr.contains("error: unrecognized character"), "{stderr}");
+	assert!(stderr.contains("--> -:1:9"), "{stderr}");
+}
+
+#[test]
+fn fmt_reads_stdin() {
+	let output = run(&["fmt", "-"], "f(x)=x+1");
+
+	assert_eq!(output.status.code(), Some(0));
+	assert_eq!(String::from_utf8(output.stdout).unwrap(), "f(x) = x + 1\n");
+
+	let output = run(&["fmt", "-"], "f(x)=x+1 # note");
+
+	assert_eq!(output.status.code(), Some(0));
+	assert_eq!(
+		String::from_utf8(output.stdout).unwrap(),
+		"f(x) = x + 1 # note\n"
+	);
+
+	let output = run(&["fmt", "-"], "f(x)=g(x, # note\n  1)");
+	let stderr = String::from_utf8(output.stderr).unwrap();
+
+	assert_eq!(output.status.code(), Some(1));
+	assert!(output.stdout.is_empty());
+	assert!(stderr.contains("error: misplaced comment"), "{stderr}");
+}
+
+#[test]
+fn usage_errors() {
+	assert_eq!(run(&[], "").status.code(), Some(2));
+	assert_eq!(run(&["ast", "a", "b"], "").status.code(), Some(2));
+	assert_eq!(run(&["tokens", "missing.rosy"], "").status.code(), Some(2));
+}
@@ -0,0 +1,145 @@
+use std::fs;
+use std::path::PathBuf;
+
+use rosy_parse::parser::ast::*;
+use rosy_parse::parser::cst::{LeafKind, NodeKind, SyntaxElement, TriviaKind};
+use rosy_parse::parser::ids::node_map;
+
+fn corpus() -> Vec<(PathBuf, String)> {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+
+	let mut files: Vec<_> = fs::read_dir(dir)
+		.unwrap()
+		.map(|entry| entry.unwrap().path())
+		.filter(|path| path.extension().is_some_and(|ext| ext == "rosy"))
+		.map(|path| {
+			let source = fs::read_to_string(&path).unwrap();
+			(path, source)
+		})
+		.collect();
+
+	files.sort();
+	assert!(!files.is_empty());
+
+	files
+}
+
+fn assert_lossless(source: &str, name: &str) {
+	let (_, cst, _, _) = rosy_parse::parse_with_cst(source);
+
+	assert_eq!(cst.to_string(), source, "{name}");
+	assert_eq!(cst.span().1, source.len(), "{name}");
+
+	for token in cst.tokens() {
+		let (start, end) = (token.span().0, token.span().1);
+		assert_eq!(token.text(), &source[start..end], "{name}");
+	}
+}
+
+#[test]
+fn corpus_round_trips() {
+	for (path, source) in corpus() {
+		let name = path.display().to_string();
+
+		assert_lossless(&source, &name);
+		assert_lossless(&source.replace('\n', "\r\n"), &name);
+	}
+}
+
+#[test]
+fn truncated_corpus_round_trips() {
+	for (path, source) in corpus() {
+		let name = path.display().to_string();
+
+		for (end, _) in source.char_indices() {
+			assert_lossless(&source[..end], &format!("{name}[..{end}]"));
+		}
+	}
+}
+
+#[test]
+fn layout_errors_round_trip() {
+	// the indentation error spans the whole line, overlapping `then`
+	assert_lossless("do\n    then\n->1", "insufficient indent");
+	assert_lossless("f() =\n    a\n  b\n", "inconsistent indent");
+}
+
+#[test]
+fn comments_are_trivia() {
+	let source = "## docs\nf() = 1 # line\n#[ block ]#\ng() = 2 ## trailing\n";
+	let (_, cst, _, errors) = rosy_parse::parse_with_cst(source);
+
+	assert!(errors.is_empty());
+
+	let trivia: Vec<_> = cst
+		.tokens()
+		.into_iter()
+		.filter(|token| token.is_trivia())
+		.filter(|token| !matches!(token.kind(), LeafKind::Trivia(TriviaKind::Whitespace)))
+		.map(|token| (token.kind().clone(), String::from(token.text())))
+		.collect();
+
+	let expected = vec![
+		(
+			LeafKind::Trivia(TriviaKind::DocComment),
+			String::from("## docs"),
+		),
+		(
+			LeafKind::Trivia(TriviaKind::Comment),
+			String::from("# line"),
+		),
+		(
+			LeafKind::Trivia(TriviaKind::Comment),
+			String::from("#[ block ]#"),
+		),
+		(
+			LeafKind::Trivia(TriviaKind::Comment),
+			String::from("## trailing"),
+		),
+	];
+
+	assert_eq!(trivia, expected);
+}
+
+#[test]
+fn every_node_maps_to_its_syntax() {
+	for (path, source) in corpus() {
+		for (end, _) in source.char_indices() {
+			let name = format!("{}[..{end}]", path.display());
+			let (ast, _, nodes, _) = rosy_parse::parse_with_cst(&source[..end]);
+
+			for (id, node) in node_map(&ast) {
+				let element = nodes
+					.get(&id)
+					.unwrap_or_else(|| panic!("{name}: {id:?} has no syntax"));
+
+				assert_eq!(element.span().0, node.span().0, "{name}: {id:?}");
+			}
+		}
+	}
+}
+
+#[test]
+fn syntax_keeps_trivia_of_nodes() {
+	let source = "f(x) = (x #[ one ]# + 1)";
+	let (ast, _, nodes, _) = rosy_parse::parse_with_cst(source);
+
+	let DeclKind::Fn(fn_def) = &ast[0].kind else {
+		panic!("expected function definition");
+	};
+	let SyntaxElement::Node(body) = &nodes[&fn_def.body.id] else {
+		panic!("expected node");
+	};
+	let SyntaxElement::Token(param) = &nodes[&fn_def.params[0].id] else {
+		panic!("expected token");
+	};
+
+	assert_eq!(body.kind(), NodeKind::Paren);
+	assert_eq!(body.to_string(), "(x #[ one ]# + 1)");
+	assert_eq!(param.text(), "x");
+
+	let ExprKind::Binary(_, lhs, _) = &fn_def.body.kind else {
+		panic!("expected binary expression");
+	};
+	assert_eq!(nodes[&lhs.id].span().to_string(), "8..9");
+}
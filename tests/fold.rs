@@ -0,0 +1,68 @@
+use rosy_parse::format::format_ast;
+use rosy_parse::parser::ast::*;
+use rosy_parse::parser::fold::{self, Fold};
+
+// folds integer additions and multiplications whose operands are literals
+struct ConstantFold;
+
+impl Fold for ConstantFold {
+	fn fold_expr(&mut self, expr: Expr) -> Expr {
+		let Expr { id, kind, span } = fold::fold_expr(self, expr);
+
+		let kind = match kind {
+			ExprKind::Binary(bin_op, lhs, rhs) => {
+				match (&bin_op.lexeme[..], &lhs.kind, &rhs.kind) {
+					("+", ExprKind::Integer(l), ExprKind::Integer(r)) => ExprKind::Integer(l + r),
+					("*", ExprKind::Integer(l), ExprKind::Integer(r)) => ExprKind::Integer(l * r),
+					_ => ExprKind::Binary(bin_op, lhs, rhs),
+				}
+			},
+			kind => kind,
+		};
+
+		Expr { id, kind, span }
+	}
+}
+
+#[test]
+fn constant_folding() {
+	let ast = rosy_parse::parse("f(x) =\n  y = 1 + 2 * 3\n  x + (4 + 5)\n").unwrap();
+	let ast = ConstantFold.fold_ast(ast);
+
+	assert_eq!(format_ast(&ast), "f(x) =\n  y = 7\n  x + 9\n");
+
+	let DeclKind::Fn(fn_def) = &ast[0].kind else {
+		panic!("expected a function");
+	};
+	let ExprKind::Block(stmts) = &fn_def.body.kind else {
+		panic!("expected a block");
+	};
+	let StmtKind::VarDef(var_def) = &stmts[0].kind else {
+		panic!("expected a variable definition");
+	};
+
+	// the folded literal keeps the span of the expression it replaced
+	assert_eq!(var_def.value.span.to_string(), "13..22");
+}
+
+#[test]
+fn default_fold_is_identity() {
+	let source = "\
+infixl 6 <+>
+## doc
+f: (Int, Int) -> Int
+f(a, b) = if a < b then -a <+> b else while a do g()
+h() =
+  x: Int
+  x = 1.5
+  x
+";
+	let ast = rosy_parse::parse(source).unwrap();
+
+	struct Identity;
+
+	impl Fold for Identity {}
+
+	// spans and ids take part in the comparison
+	assert_eq!(Identity.fold_ast(ast.clone()), ast);
+}
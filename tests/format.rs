@@ -0,0 +1,196 @@
+use std::fs;
+use std::path::PathBuf;
+
+use indoc::indoc;
+use rosy_parse::format_source;
+
+fn format_expr(source: &str) -> String {
+	let formatted = format_source(&format!("f() = {source}")).unwrap();
+
+	String::from(formatted.trim_end().strip_prefix("f() = ").unwrap())
+}
+
+fn assert_idempotent(source: &str, name: &str) {
+	let formatted = format_source(source).unwrap();
+	let reformatted = format_source(&formatted).unwrap_or_else(|err| {
+		panic!(
+			"{name}: formatted source does not parse: {}\n{formatted}",
+			err.title
+		)
+	});
+
+	assert_eq!(reformatted, formatted, "{name}");
+}
+
+#[test]
+fn corpus_is_idempotent() {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+
+	for entry in fs::read_dir(dir).unwrap() {
+		let path = entry.unwrap().path();
+
+		// the error corpus only exercises the lossless tree
+		if path.file_stem().is_some_and(|stem| stem == "errors") {
+			continue;
+		}
+
+		let source = fs::read_to_string(&path).unwrap();
+		assert_idempotent(&source, &path.display().to_string());
+	}
+}
+
+#[test]
+fn parentheses_follow_precedence() {
+	let cases = [
+		("(a + b) * c", "(a + b) * c"),
+		("a + (b * c)", "a + b * c"),
+		("(a - b) - c", "a - b - c"),
+		("a - (b - c)", "a - (b - c)"),
+		("a ** (b ** c)", "a ** b ** c"),
+		("(a ** b) ** c", "(a ** b) ** c"),
+		("-(a ** b)", "-a ** b"),
+		("(-a) ** b", "(-a) ** b"),
+		("(-a) * b", "-a * b"),
+		("-(a * b)", "-(a * b)"),
+		("a <> (-b) <> c", "a <> (-b) <> c"),
+		("- (-a)", "- -a"),
+		("(f + g)(x)", "(f + g)(x)"),
+		("(f(x))(y)", "f(x)(y)"),
+		("(if a then b else c) + d", "(if a then b else c) + d"),
+		("((((a))))", "a"),
+	];
+
+	for (source, expected) in cases {
+		assert_eq!(format_expr(source), expected, "{source}");
+		assert_idempotent(&format!("f() = {source}"), source);
+	}
+}
+
+#[test]
+fn fixity_declarations_drive_parentheses() {
+	let source = indoc! {"
+		infixr 5 <>
+		f() = (a <> b) <> (c <> d)"
+	};
+
+	let expected = indoc! {"
+		infixr 5 <>
+
+		f() = (a <> b) <> c <> d
+	"};
+
+	assert_eq!(format_source(source).unwrap(), expected);
+}
+
+#[test]
+fn layout_and_spacing() {
+	let source = indoc! {"
+		## Counts down.
+		count(n)=
+		    while n>0 do
+		        n=n-1
+		    if n==0 then
+		           true
+		    else false
+		count:Int->Int
+		id: (Int) -> Int
+		id(x) = x
+		s() = f(\"a\\\"b\\n\", '\\'', 1.5e-3, -1)"
+	};
+
+	let expected = indoc! {r#"
+		## Counts down.
+		count(n) =
+		  while n > 0 do
+		    n = n - 1
+		  if n == 0 then
+		    true
+		  else false
+
+		count: Int -> Int
+
+		id: Int -> Int
+		id(x) = x
+
+		s() = f("a\"b\n", '\'', 0.0015, -1)
+	"#};
+
+	assert_eq!(format_source(source).unwrap(), expected);
+	assert_idempotent(source, "layout");
+}
+
+#[test]
+fn long_calls_wrap() {
+	let args: Vec<_> = (0..12).map(|i| format!("argument_{i}")).collect();
+	let source = format!("f() = g(h({}), x)", args.join(", "));
+
+	let expected = format!("f() = g(\n  h(\n    {}),\n  x)\n", args.join(",\n    "));
+
+	assert_eq!(format_source(&source).unwrap(), expected);
+	assert_idempotent(&source, "long call");
+
+	assert_eq!(format_source("f() = g(a, b)").unwrap(), "f() = g(a, b)\n");
+}
+
+#[test]
+fn comments_are_kept() {
+	let source = indoc! {"
+		# header
+		f(x)=x+1 # trailing
+		g() =
+		  # before
+		  a = -#[c]# x +(#[d]# y)
+		  if a then
+		    b # inside
+		  else c
+		# footer
+	"};
+
+	let expected = indoc! {"
+		# header
+		f(x) = x + 1 # trailing
+
+		g() =
+		  # before
+		  a = - #[c]# x + #[d]# y
+		  if a then
+		    b # inside
+		  else c
+		# footer
+	"};
+
+	assert_eq!(format_source(source).unwrap(), expected);
+	assert_idempotent(source, "comments");
+}
+
+#[test]
+fn misplaced_comments_are_errors() {
+	let cases = [
+		"f() = g(a, # c\n  b)",
+		"f() = g(a #[c]#)",
+		"f() =\n  if a then b # c\n  else d",
+	];
+
+	for source in cases {
+		let err = format_source(source).unwrap_err();
+		assert_eq!(err.title, "misplaced comment", "{source}");
+	}
+}
+
+#[test]
+fn floats_read_back() {
+	let cases = [
+		("0.1", "0.1"),
+		("1e300", "1e300"),
+		("2.5e-300", "2.5e-300"),
+		("1e-999", "0.0"),
+	];
+
+	for (source, expected) in cases {
+		assert_eq!(format_expr(source), expected, "{source}");
+		assert_eq!(format_expr(expected), expected, "{source}");
+	}
+
+	let err = format_source("f() = 1e999").unwrap_err();
+	assert_eq!(err.title, "float literal too large");
+}
@@ -0,0 +1,186 @@
+use indoc::indoc;
+use rosy_parse::common::inspect::inspect_ast;
+
+// pins the rendering of every node kind, so changes to the visitors or the
+// tree drawing show up here
+#[test]
+fn inspect_every_node_kind() {
+	let source = indoc! {r#"
+		## Doc for the fixity.
+		infixl 6 <+>
+
+		## Identity.
+		##
+		## Second paragraph.
+		id: Int -> Int
+		id(x) = x
+
+		f() =
+		  ## A local type.
+		  y: (Int, Bool) -> Int
+		  z = -g() <+> 1.5
+		  if true then "s" else 'c'
+		  if z then
+		    z
+		  while z do z
+		  h(y, 2)
+		oops
+		m() = a + `
+	"#};
+
+	let expected = indoc! {r#"
+		╰ AST
+		  ├ Fixity
+		  │ │╰ 23..35
+		  │ ├ Docs
+		  │ │ ╰ Doc("Doc for the fixity.")
+		  │ │    ╰ 0..22
+		  │ ├ Associativity(Left)
+		  │ ├ Precedence(6)
+		  │ ╰ Operators
+		  │   ╰ Identifier(<+>)
+		  │      ╰ 32..35
+		  ├ Type Declaration
+		  │ │╰ 74..88
+		  │ ├ Docs
+		  │ │ ├ Doc("Identity.")
+		  │ │ │  ╰ 37..49
+		  │ │ ├ Doc("")
+		  │ │ │  ╰ 50..52
+		  │ │ ╰ Doc("Second paragraph.")
+		  │ │    ╰ 53..73
+		  │ ├ Identifier
+		  │ │ ╰ Identifier(id)
+		  │ │    ╰ 74..76
+		  │ ╰ Type
+		  │   │╰ 78..88
+		  │   ╰ Int -> Int
+		  ├ Define
+		  │ │╰ 89..98
+		  │ ├ Function
+		  │ │ ╰ Identifier(id)
+		  │ │    ╰ 89..91
+		  │ ├ Parameters
+		  │ │ ╰ Identifier(x)
+		  │ │    ╰ 92..93
+		  │ ╰ Body
+		  │   ╰ Identifier(x)
+		  │      ╰ 97..98
+		  ├ Define
+		  │ │╰ 100..239
+		  │ ├ Function
+		  │ │ ╰ Identifier(f)
+		  │ │    ╰ 100..101
+		  │ ├ Parameters
+		  │ │
+		  │ ╰ Body
+		  │   ╰ Block
+		  │     │╰ 127..239
+		  │     ├ Type Declaration
+		  │     │ │╰ 127..148
+		  │     │ ├ Docs
+		  │     │ │ ╰ Doc("A local type.")
+		  │     │ │    ╰ 108..124
+		  │     │ ├ Identifier
+		  │     │ │ ╰ Identifier(y)
+		  │     │ │    ╰ 127..128
+		  │     │ ╰ Type
+		  │     │   │╰ 130..148
+		  │     │   ╰ (Int, Bool) -> Int
+		  │     ├ Define
+		  │     │ │╰ 151..167
+		  │     │ ├ Variable
+		  │     │ │ ╰ Identifier(z)
+		  │     │ │    ╰ 151..152
+		  │     │ ╰ Value
+		  │     │   ╰ Binary
+		  │     │     │╰ 155..167
+		  │     │     ├ Operator(<+>)
+		  │     │     │  ╰ 160..163
+		  │     │     ├ Left
+		  │     │     │ ╰ Unary
+		  │     │     │   │╰ 155..159
+		  │     │     │   ├ Operator(-)
+		  │     │     │   │  ╰ 155..156
+		  │     │     │   ╰ Operand
+		  │     │     │     ╰ Call
+		  │     │     │       │╰ 156..159
+		  │     │     │       ├ Function
+		  │     │     │       │ ╰ Identifier(g)
+		  │     │     │       │    ╰ 156..157
+		  │     │     │       ╰ Arguments
+		  │     │     │
+		  │     │     ╰ Right
+		  │     │       ╰ Float(1.5)
+		  │     │          ╰ 164..167
+		  │     ├ If
+		  │     │ │╰ 170..195
+		  │     │ ├ Condition
+		  │     │ │ ╰ Boolean(true)
+		  │     │ │    ╰ 173..177
+		  │     │ ├ Then
+		  │     │ │ ╰ String("s")
+		  │     │ │    ╰ 183..186
+		  │     │ ╰ Else
+		  │     │   ╰ Char('c')
+		  │     │      ╰ 192..195
+		  │     ├ If
+		  │     │ │╰ 198..216
+		  │     │ ├ Condition
+		  │     │ │ ╰ Identifier(z)
+		  │     │ │    ╰ 201..202
+		  │     │ ╰ Then
+		  │     │   ╰ Block
+		  │     │     │╰ 212..216
+		  │     │     ╰ Identifier(z)
+		  │     │        ╰ 212..213
+		  │     ├ While
+		  │     │ │╰ 216..228
+		  │     │ ├ Condition
+		  │     │ │ ╰ Identifier(z)
+		  │     │ │    ╰ 222..223
+		  │     │ ╰ Body
+		  │     │   ╰ Identifier(z)
+		  │     │      ╰ 227..228
+		  │     ╰ Call
+		  │       │╰ 231..238
+		  │       ├ Function
+		  │       │ ╰ Identifier(h)
+		  │       │    ╰ 231..232
+		  │       ╰ Arguments
+		  │         ├ Identifier(y)
+		  │         │  ╰ 233..234
+		  │         ╰ Integer(2)
+		  │            ╰ 236..237
+		  ├ Error
+		  │  ╰ 239..243
+		  ╰ Define
+		    │╰ 244..255
+		    ├ Function
+		    │ ╰ Identifier(m)
+		    │    ╰ 244..245
+		    ├ Parameters
+		    │
+		    ╰ Body
+		      ╰ Binary
+		        │╰ 250..255
+		        ├ Operator(+)
+		        │  ╰ 252..253
+		        ├ Left
+		        │ ╰ Identifier(a)
+		        │    ╰ 250..251
+		        ╰ Right
+		          ╰ Error
+		             ╰ 254..255
+	"#};
+
+	let (ast, errors) = rosy_parse::parse_recovering(source);
+	assert_eq!(errors.len(), 2);
+
+	// empty argument lists end in padding, which is not worth pinning
+	let inspected = inspect_ast(&ast);
+	let actual: Vec<_> = inspected.lines().map(str::trim_end).collect();
+	let expected: Vec<_> = expected.lines().collect();
+
+	assert_eq!(actual, expected);
+}
@@ -0,0 +1,73 @@
+#![cfg(feature = "serde")]
+
+use std::fs;
+use std::path::PathBuf;
+
+use rosy_parse::parser::ast::Ast;
+use rosy_parse::Token;
+
+#[test]
+fn corpus_round_trips() {
+	let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/corpus");
+
+	for entry in fs::read_dir(dir).unwrap() {
+		let path = entry.unwrap().path();
+		let source = fs::read_to_string(&path).unwrap();
+
+		let (ast, _) = rosy_parse::parse_recovering(&source);
+
+		let json = serde_json::to_string(&ast).unwrap();
+		let deserialized: Ast = serde_json::from_str(&json).unwrap();
+
+		assert_eq!(deserialized, ast, "{}", path.display());
+	}
+}
+
+#[test]
+fn json_shape() {
+	let ast = rosy_parse::parse("f(x) = -x").unwrap();
+
+	let expected = serde_json::json!([{
+		"id": 0,
+		"kind": {
+			"type": "Fn",
+			"value": {
+				"func": { "id": 1, "name": "f", "span": { "start": 0, "end": 1 } },
+				"params": [{ "id": 2, "name": "x", "span": { "start": 2, "end": 3 } }],
+				"body": {
+					"id": 3,
+					"kind": {
+						"type": "Unary",
+						"value": [
+							{ "lexeme": "-", "span": { "start": 7, "end": 8 } },
+							{
+								"id": 4,
+								"kind": { "type": "Identifier", "value": "x" },
+								"span": { "start": 8, "end": 9 },
+							},
+						],
+					},
+					"span": { "start": 7, "end": 9 },
+				},
+				"docs": [],
+				"span": { "start": 0, "end": 9 },
+			},
+		},
+		"span": { "start": 0, "end": 9 },
+	}]);
+
+	assert_eq!(serde_json::to_value(&ast).unwrap(), expected);
+}
+
+#[test]
+fn tokens_round_trip() {
+	let tokens: Vec<Token> = rosy_parse::tokenize("f() = 1.5")
+		.map(Result::unwrap)
+		.collect();
+
+	let json = serde_json::to_string(&tokens).unwrap();
+	let deserialized: Vec<Token> = serde_json::from_str(&json).unwrap();
+
+	assert_eq!(serde_json::to_string(&deserialized).unwrap(), json);
+	assert!(json.contains(r#"{"kind":{"type":"Float","value":1.5},"span":{"start":6,"end":9}}"#));
+}